@@ -0,0 +1,49 @@
+use ray_tracer_challenge::scene::Scene;
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "usage: render <scene.yaml> <output.ppm|output.png>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [scene_path, output_path] = args.as_slice() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    // Catch a wrong extension now rather than after a long render.
+    let extension = Path::new(output_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if !matches!(extension.as_deref(), Some("ppm" | "png")) {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+
+    let scene = match Scene::open(scene_path) {
+        Ok(scene) => scene,
+        Err(error) => {
+            eprintln!("{}: {}", scene_path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let start = Instant::now();
+    let (image, stats) = scene.camera.render_with_stats(&scene.world);
+    eprintln!(
+        "rendered {}x{} in {:.2?} on {} threads ({} primitive tests)",
+        scene.camera.hsize(),
+        scene.camera.vsize(),
+        start.elapsed(),
+        scene.camera.threads(),
+        stats.primitive_tests
+    );
+
+    if let Err(error) = image.save(output_path) {
+        eprintln!("{}: {}", output_path, error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,398 @@
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::EPSILON;
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    /// A box containing nothing, which grows to fit whatever is added to it.
+    pub fn empty() -> Self {
+        Self {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+    }
+
+    pub fn add_point(&mut self, point: Point) {
+        self.min = Point::new(
+            self.min.x.min(point.x),
+            self.min.y.min(point.y),
+            self.min.z.min(point.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(point.x),
+            self.max.y.max(point.y),
+            self.max.z.max(point.z),
+        );
+    }
+
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if other.is_empty() {
+            return;
+        }
+        self.add_point(other.min);
+        self.add_point(other.max);
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+
+    /// The box around all eight corners of this box after transforming them.
+    pub fn transform(&self, matrix: &Matrix<4>) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let mut result = BoundingBox::empty();
+        let mut unbounded = [false; 3];
+
+        for x in [self.min.x, self.max.x] {
+            for y in [self.min.y, self.max.y] {
+                for z in [self.min.z, self.max.z] {
+                    let corner = transform_corner(matrix, [x, y, z]);
+                    for (axis, value) in corner.iter().enumerate() {
+                        unbounded[axis] |= value.is_nan();
+                    }
+                    result.add_point(Point::from(corner));
+                }
+            }
+        }
+
+        // Infinite extents pointing in opposite directions cancel out to NaN,
+        // which can only mean the box is unbounded along that axis.
+        if unbounded[0] {
+            result.min.x = f64::NEG_INFINITY;
+            result.max.x = f64::INFINITY;
+        }
+        if unbounded[1] {
+            result.min.y = f64::NEG_INFINITY;
+            result.max.y = f64::INFINITY;
+        }
+        if unbounded[2] {
+            result.min.z = f64::NEG_INFINITY;
+            result.max.z = f64::INFINITY;
+        }
+        result
+    }
+
+    /// Slab test of `ray` against the box.
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        tmin <= tmax && tmax >= 0.0
+    }
+}
+
+/// Multiplies a point by `matrix`, treating `0 * inf` as 0 so unbounded
+/// boxes stay unbounded only along the axes they extend into.
+fn transform_corner(matrix: &Matrix<4>, corner: [f64; 3]) -> [f64; 3] {
+    let mut result = [0.0; 3];
+    for (row, value) in result.iter_mut().enumerate() {
+        *value = matrix[row][3];
+        for (col, coordinate) in corner.iter().enumerate() {
+            if matrix[row][col] != 0.0 {
+                *value += matrix[row][col] * coordinate;
+            }
+        }
+    }
+    result
+}
+
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    // An infinite box along an axis the ray runs parallel to yields NaN; it never excludes a hit.
+    let tmin = if tmin.is_nan() {
+        f64::NEG_INFINITY
+    } else {
+        tmin
+    };
+    let tmax = if tmax.is_nan() { f64::INFINITY } else { tmax };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vector;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+
+    #[test]
+    fn should_create_an_empty_bounding_box() {
+        let bounds = BoundingBox::empty();
+
+        assert!(bounds.is_empty());
+        assert_eq!(bounds.min.x, f64::INFINITY);
+        assert_eq!(bounds.max.x, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn should_add_points_to_an_empty_bounding_box() {
+        let mut bounds = BoundingBox::empty();
+
+        bounds.add_point(Point::new(-5.0, 2.0, 0.0));
+        bounds.add_point(Point::new(7.0, 0.0, -3.0));
+
+        assert_eq!(bounds.min, Point::new(-5.0, 0.0, -3.0));
+        assert_eq!(bounds.max, Point::new(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn should_merge_one_bounding_box_into_another() {
+        let mut a = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let b = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+
+        a.merge(&b);
+
+        assert_eq!(a.min, Point::new(-5.0, -7.0, -2.0));
+        assert_eq!(a.max, Point::new(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn should_check_if_a_box_contains_a_point() {
+        let bounds = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let cases = [
+            (Point::new(5.0, -2.0, 0.0), true),
+            (Point::new(11.0, 4.0, 7.0), true),
+            (Point::new(8.0, 1.0, 3.0), true),
+            (Point::new(3.0, 0.0, 3.0), false),
+            (Point::new(8.0, -4.0, 3.0), false),
+            (Point::new(8.0, 1.0, -1.0), false),
+            (Point::new(13.0, 1.0, 3.0), false),
+            (Point::new(8.0, 5.0, 3.0), false),
+            (Point::new(8.0, 1.0, 8.0), false),
+        ];
+
+        for (point, expected) in cases {
+            assert_eq!(bounds.contains_point(point), expected);
+        }
+    }
+
+    #[test]
+    fn should_check_if_a_box_contains_a_box() {
+        let bounds = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let cases = [
+            (Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0), true),
+            (Point::new(6.0, -1.0, 1.0), Point::new(10.0, 3.0, 6.0), true),
+            (
+                Point::new(4.0, -3.0, -1.0),
+                Point::new(10.0, 3.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(6.0, -1.0, 1.0),
+                Point::new(12.0, 5.0, 8.0),
+                false,
+            ),
+        ];
+
+        for (min, max, expected) in cases {
+            assert_eq!(bounds.contains_box(&BoundingBox::new(min, max)), expected);
+        }
+    }
+
+    #[test]
+    fn should_transform_a_bounding_box() {
+        let bounds = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let matrix = Matrix::rotation_x(PI / 4.0) * Matrix::rotation_y(PI / 4.0);
+
+        let actual = bounds.transform(&matrix);
+
+        assert_eq!(
+            actual.min,
+            Point::new(-SQRT_2, -1.0 - FRAC_1_SQRT_2, -1.0 - FRAC_1_SQRT_2)
+        );
+        assert_eq!(
+            actual.max,
+            Point::new(SQRT_2, 1.0 + FRAC_1_SQRT_2, 1.0 + FRAC_1_SQRT_2)
+        );
+    }
+
+    #[test]
+    fn should_keep_an_infinite_box_unbounded_only_where_it_was() {
+        let bounds = BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+
+        let actual = bounds.transform(&Matrix::translation(0.0, 2.0, 0.0));
+
+        assert_eq!(actual.min.y, 2.0);
+        assert_eq!(actual.max.y, 2.0);
+        assert_eq!(actual.min.x, f64::NEG_INFINITY);
+        assert_eq!(actual.max.z, f64::INFINITY);
+    }
+
+    #[test]
+    fn should_intersect_a_ray_with_a_cubic_bounding_box_at_the_origin() {
+        let bounds = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let cases = [
+            (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), true),
+            (Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), true),
+            (Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0), true),
+            (Point::new(0.5, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0), true),
+            (Point::new(0.5, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0), true),
+            (Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), true),
+            (
+                Point::new(-2.0, 0.0, 0.0),
+                Vector::new(2.0, 4.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(0.0, -2.0, 0.0),
+                Vector::new(6.0, 2.0, 4.0),
+                false,
+            ),
+            (
+                Point::new(0.0, 0.0, -2.0),
+                Vector::new(4.0, 6.0, 2.0),
+                false,
+            ),
+            (
+                Point::new(2.0, 0.0, 2.0),
+                Vector::new(0.0, 0.0, -1.0),
+                false,
+            ),
+            (
+                Point::new(0.0, 2.0, 2.0),
+                Vector::new(0.0, -1.0, 0.0),
+                false,
+            ),
+            (
+                Point::new(2.0, 2.0, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                false,
+            ),
+        ];
+
+        for (origin, direction, expected) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(bounds.intersects(&ray), expected);
+        }
+    }
+
+    #[test]
+    fn should_intersect_a_ray_with_a_non_cubic_bounding_box() {
+        let bounds = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let cases = [
+            (
+                Point::new(15.0, 1.0, 2.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                true,
+            ),
+            (
+                Point::new(-5.0, -1.0, 4.0),
+                Vector::new(1.0, 0.0, 0.0),
+                true,
+            ),
+            (Point::new(7.0, 6.0, 5.0), Vector::new(0.0, -1.0, 0.0), true),
+            (Point::new(9.0, -5.0, 6.0), Vector::new(0.0, 1.0, 0.0), true),
+            (
+                Point::new(8.0, 2.0, 12.0),
+                Vector::new(0.0, 0.0, -1.0),
+                true,
+            ),
+            (Point::new(6.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(8.0, 1.0, 3.5), Vector::new(0.0, 0.0, 1.0), true),
+            (
+                Point::new(9.0, -1.0, -8.0),
+                Vector::new(2.0, 4.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(8.0, 3.0, -4.0),
+                Vector::new(6.0, 2.0, 4.0),
+                false,
+            ),
+            (
+                Point::new(9.0, -1.0, -2.0),
+                Vector::new(4.0, 6.0, 2.0),
+                false,
+            ),
+            (
+                Point::new(4.0, 0.0, 9.0),
+                Vector::new(0.0, 0.0, -1.0),
+                false,
+            ),
+            (
+                Point::new(8.0, 6.0, -1.0),
+                Vector::new(0.0, -1.0, 0.0),
+                false,
+            ),
+            (
+                Point::new(12.0, 5.0, 4.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                false,
+            ),
+        ];
+
+        for (origin, direction, expected) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(bounds.intersects(&ray), expected);
+        }
+    }
+
+    #[test]
+    fn should_not_intersect_a_box_behind_the_ray() {
+        let bounds = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(!bounds.intersects(&ray));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::stats::{self, IntersectionStats};
+use crate::transform::{InvertibleTransform, SingularTransformError};
+use crate::world::World;
+use std::sync::Mutex;
+use std::thread;
+
+/// Rows per tile. Tiles span the full width of the image, so each one is a
+/// contiguous chunk of the canvas that a single thread can own.
+pub const TILE_ROWS: usize = 8;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Camera {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    transform: InvertibleTransform,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+    threads: usize,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: InvertibleTransform::identity(),
+            half_width,
+            half_height,
+            pixel_size: (half_width * 2.0) / hsize as f64,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    pub fn hsize(&self) -> usize {
+        self.hsize
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.vsize
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    pub fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = InvertibleTransform::new(transform);
+    }
+
+    pub fn try_set_transform(
+        &mut self,
+        transform: Matrix<4>,
+    ) -> Result<(), SingularTransformError> {
+        self.transform = InvertibleTransform::try_new(transform)?;
+        Ok(())
+    }
+
+    pub fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    /// How many threads `render` uses, all available cores by default.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    /// Creates a ray from the camera through the center of the given pixel.
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        let xoffset = (px as f64 + 0.5) * self.pixel_size;
+        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+
+        // The camera looks toward -z, so +x is to the left.
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        let inverse = *self.transform.inverse();
+        let pixel = inverse * Point::new(world_x, world_y, -1.0);
+        let origin = inverse * Point::new(0.0, 0.0, 0.0);
+        let direction = (pixel - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        self.render_with_stats(world).0
+    }
+
+    /// Renders `world` and reports how many intersection tests that took.
+    ///
+    /// Tiles are handed out to the worker threads as they become free. Every
+    /// pixel only depends on its own ray, so the result is the same whatever
+    /// the number of threads or the order the tiles are finished in.
+    pub fn render_with_stats(&self, world: &World) -> (Canvas, IntersectionStats) {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let tiles = Mutex::new(image.rows_mut(TILE_ROWS).enumerate());
+
+        let render_stats = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        // Worker threads are fresh, so their counters start at zero.
+                        loop {
+                            let next = tiles.lock().unwrap().next();
+                            let Some((tile, pixels)) = next else {
+                                break;
+                            };
+                            self.render_tile(world, tile * TILE_ROWS, pixels);
+                        }
+                        stats::take()
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .fold(IntersectionStats::default(), |acc, stats| acc + stats)
+        });
+
+        (image, render_stats)
+    }
+
+    /// Fills `pixels`, the rows of the image starting at `first_row`.
+    fn render_tile(&self, world: &World, first_row: usize, pixels: &mut [Color]) {
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            let x = index % self.hsize;
+            let y = first_row + index / self.hsize;
+            *pixel = world.color_at(&self.ray_for_pixel(x, y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equal;
+    use crate::vector::Vector;
+    use crate::world::tests::default_world;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_construct_a_camera() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(camera.hsize(), 160);
+        assert_eq!(camera.vsize(), 120);
+        assert_eq!(camera.field_of_view(), PI / 2.0);
+        assert_eq!(*camera.transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_compute_pixel_size_for_a_horizontal_canvas() {
+        let camera = Camera::new(200, 125, PI / 2.0);
+        assert!(equal(camera.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn should_compute_pixel_size_for_a_vertical_canvas() {
+        let camera = Camera::new(125, 200, PI / 2.0);
+        assert!(equal(camera.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn should_construct_a_ray_through_the_center_of_the_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel(100, 50);
+
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_construct_a_ray_through_a_corner_of_the_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel(0, 0);
+
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn should_construct_a_ray_when_the_camera_is_transformed() {
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        camera.set_transform(Matrix::rotation_y(PI / 4.0) * Matrix::translation(0.0, -2.0, 5.0));
+
+        let ray = camera.ray_for_pixel(100, 50);
+
+        assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(
+            ray.direction,
+            Vector::new(2.0_f64.sqrt() / 2.0, 0.0, -(2.0_f64.sqrt() / 2.0))
+        );
+    }
+
+    #[test]
+    fn should_render_a_world_with_a_camera() {
+        let world = default_world();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        camera.set_transform(Matrix::view_transform(from, to, up));
+
+        let image = camera.render(&world);
+
+        assert_eq!(image.get_pixel(5, 5), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn should_count_intersection_tests_while_rendering() {
+        let world = default_world();
+        let camera = Camera::new(11, 11, PI / 2.0);
+
+        let (_, render_stats) = camera.render_with_stats(&world);
+
+        // Every primary ray tests both spheres, hits also cast a shadow ray.
+        assert!(render_stats.primitive_tests >= 11 * 11 * 2);
+    }
+
+    #[test]
+    fn should_render_the_same_image_whatever_the_thread_count() {
+        let world = default_world();
+        let mut camera = Camera::new(23, 19, PI / 3.0);
+        camera.set_transform(Matrix::view_transform(
+            Point::new(0.0, 1.5, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ));
+
+        camera.set_threads(1);
+        let (single, single_stats) = camera.render_with_stats(&world);
+        camera.set_threads(5);
+        let (multi, multi_stats) = camera.render_with_stats(&world);
+
+        assert_eq!(single.to_ppm(), multi.to_ppm());
+        assert_eq!(single_stats, multi_stats);
+    }
+
+    #[test]
+    fn should_use_at_least_one_thread() {
+        let mut camera = Camera::new(10, 10, PI / 2.0);
+
+        camera.set_threads(0);
+
+        assert_eq!(camera.threads(), 1);
+    }
+}
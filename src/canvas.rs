@@ -1,8 +1,22 @@
 use crate::color::Color;
+use crate::png::{self, PngDepth};
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
+/// The longest line an ASCII PPM file may contain.
+const PPM_LINE_LENGTH: usize = 70;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// P3, human readable decimal values.
+    Ascii,
+    /// P6, one raw byte per channel.
+    Binary,
+}
+
+#[derive(Debug, Clone)]
 pub struct Canvas {
     width: usize,
     pub height: usize,
@@ -18,6 +32,10 @@ impl Canvas {
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
     fn with_color(width: usize, height: usize, color: Color) -> Canvas {
         Canvas {
             width,
@@ -38,58 +56,133 @@ impl Canvas {
         self.pixels[index] = color;
     }
 
+    /// Splits the canvas into disjoint chunks of `rows` full rows each, top to bottom.
+    pub fn rows_mut(&mut self, rows: usize) -> std::slice::ChunksMut<'_, Color> {
+        self.pixels.chunks_mut((self.width * rows).max(1))
+    }
+
     pub fn to_ppm_header(&self) -> String {
-        format!("P3\n{} {}\n255\n", self.width, self.height)
+        self.ppm_header(PpmFormat::Ascii)
     }
 
-    pub fn write_to_file(&self) -> std::io::Result<()> {
-        let mut buffer = File::create("foo.ppm")?;
-        write!(buffer, "{}", self.to_ppm())
+    fn ppm_header(&self, format: PpmFormat) -> String {
+        let magic = match format {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary => "P6",
+        };
+        format!("{}\n{} {}\n255\n", magic, self.width, self.height)
     }
 
-    pub fn to_ppm_body(&self) -> String {
-        let mut data = self
-            .pixels
-            .iter()
-            .map(Color::to_true_color)
-            .flat_map(|pixel| pixel.iter().map(u8::to_string).collect::<Vec<String>>())
-            .collect::<Vec<String>>();
-
-        let mut body = String::with_capacity(self.width * self.height); // can we find out the exact size cheaply?
-        let mut char_count = 0;
-        let mut pixel_count = 0;
-
-        for (index, s) in data.iter_mut().enumerate() {
-            s.push(' ');
-
-            char_count += s.chars().count();
-
-            if char_count > 69 {
-                body.pop();
-                body.push('\n');
-                char_count = 0;
-            }
+    /// Writes the canvas as a PPM image, one row at a time.
+    pub fn write_ppm<W: Write>(&self, mut w: W, format: PpmFormat) -> io::Result<()> {
+        w.write_all(self.ppm_header(format).as_bytes())?;
+        self.write_ppm_body(&mut w, format)?;
+        w.flush()
+    }
 
-            if (index + 1) % 3 == 0 {
-                pixel_count += 1;
-            }
+    /// Saves the canvas to `path`, picking the format from its extension.
+    ///
+    /// `.ppm` files are written as binary P6, which is far smaller than P3,
+    /// and `.png` files as 8-bit PNG.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
 
-            body.push_str(s);
+        match extension.as_deref() {
+            Some("ppm") => self.save_ppm(path, PpmFormat::Binary),
+            Some("png") => self.write_png(path),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "don't know which image format to use for {}",
+                    path.display()
+                ),
+            )),
+        }
+    }
+
+    /// Saves the canvas as a PPM image, for when ASCII P3 is wanted over the
+    /// binary P6 that `save` picks.
+    pub fn save_ppm(&self, path: impl AsRef<Path>, format: PpmFormat) -> io::Result<()> {
+        self.write_ppm(BufWriter::new(File::create(path)?), format)
+    }
+
+    /// Saves the canvas as an 8-bit RGB PNG.
+    pub fn write_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.encode_png(BufWriter::new(File::create(path)?), PngDepth::Eight)
+    }
+
+    pub fn encode_png<W: Write>(&self, w: W, depth: PngDepth) -> io::Result<()> {
+        let samples: Vec<u8> = match depth {
+            PngDepth::Eight => self
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_true_color())
+                .collect(),
+            PngDepth::Sixteen => self
+                .pixels
+                .iter()
+                .flat_map(|color| [color.r, color.g, color.b])
+                .flat_map(|channel| {
+                    ((channel.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes()
+                })
+                .collect(),
+        };
+        png::encode(w, self.width, self.height, depth, &samples)
+    }
 
-            if pixel_count == self.width {
-                body.pop();
-                body.push('\n');
-                pixel_count = 0;
-                char_count = 0;
+    fn write_ppm_body<W: Write>(&self, w: &mut W, format: PpmFormat) -> io::Result<()> {
+        if self.width == 0 {
+            return Ok(());
+        }
+
+        let mut line = String::with_capacity(PPM_LINE_LENGTH + 1);
+        let mut bytes = Vec::with_capacity(self.width * 3);
+
+        for row in self.pixels.chunks(self.width) {
+            bytes.clear();
+            bytes.extend(row.iter().flat_map(|color| color.to_true_color()));
+
+            match format {
+                PpmFormat::Binary => w.write_all(&bytes)?,
+                PpmFormat::Ascii => {
+                    // Every row starts on a new line and no line gets longer than 70 characters.
+                    line.clear();
+                    for value in &bytes {
+                        let value = value.to_string();
+                        if !line.is_empty() && line.len() + 1 + value.len() > PPM_LINE_LENGTH {
+                            line.push('\n');
+                            w.write_all(line.as_bytes())?;
+                            line.clear();
+                        }
+                        if !line.is_empty() {
+                            line.push(' ');
+                        }
+                        line.push_str(&value);
+                    }
+                    line.push('\n');
+                    w.write_all(line.as_bytes())?;
+                }
             }
         }
-        body
+        Ok(())
     }
 
-    fn to_ppm(&self) -> String {
-        let header = self.to_ppm_header();
-        let body = self.to_ppm_body();
-        format!("{}{}", header, body)
+    pub fn to_ppm_body(&self) -> String {
+        let mut body = Vec::new();
+        self.write_ppm_body(&mut body, PpmFormat::Ascii)
+            .expect("writing to a Vec can't fail");
+        String::from_utf8(body).expect("P3 data is ASCII")
+    }
+
+    pub fn to_ppm(&self) -> String {
+        let mut ppm = Vec::new();
+        self.write_ppm(&mut ppm, PpmFormat::Ascii)
+            .expect("writing to a Vec can't fail");
+        String::from_utf8(ppm).expect("P3 data is ASCII")
     }
 
     fn get_index(&self, x: usize, y: usize) -> usize {
@@ -101,7 +194,7 @@ impl Canvas {
 fn should_initalize_a_black_canvas() {
     let canvas = Canvas::new(3, 3);
     let is_all_black = canvas.pixels.iter().all(|x| x.is_black());
-    assert_eq!(is_all_black, true);
+    assert!(is_all_black);
 }
 
 #[test]
@@ -159,3 +252,120 @@ fn should_split_long_lines() {
 
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_split_rows_into_disjoint_chunks() {
+    let mut canvas = Canvas::new(4, 5);
+
+    for (index, chunk) in canvas.rows_mut(2).enumerate() {
+        chunk.fill(Color::new(index as f64, 0.0, 0.0));
+    }
+
+    assert_eq!(canvas.get_pixel(3, 1), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(0, 2), Color::new(1.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(2, 4), Color::new(2.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_end_ppm_files_with_a_newline() {
+    let canvas = Canvas::new(5, 3);
+
+    let ppm = canvas.to_ppm();
+
+    assert!(ppm.ends_with('\n'));
+}
+
+#[test]
+fn should_write_binary_ppm() {
+    let mut canvas = Canvas::new(2, 2);
+    canvas.set_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+    canvas.set_pixel(1, 1, Color::new(0.0, 0.5, 1.5));
+    let mut ppm = Vec::new();
+
+    canvas.write_ppm(&mut ppm, PpmFormat::Binary).unwrap();
+
+    let mut expected = b"P6\n2 2\n255\n".to_vec();
+    expected.extend([255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 127, 255]);
+    assert_eq!(ppm, expected);
+}
+
+#[test]
+fn should_save_binary_ppm_by_extension() {
+    let canvas = Canvas::with_color(3, 2, Color::new(1.0, 0.8, 0.6));
+    let path = std::env::temp_dir().join(format!("canvas-save-{}.ppm", std::process::id()));
+
+    canvas.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut expected = Vec::new();
+    canvas.write_ppm(&mut expected, PpmFormat::Binary).unwrap();
+    assert_eq!(saved, expected);
+}
+
+#[test]
+fn should_save_ascii_ppm_when_asked() {
+    let canvas = Canvas::with_color(3, 2, Color::new(1.0, 0.8, 0.6));
+    let path = std::env::temp_dir().join(format!("canvas-save-ascii-{}.ppm", std::process::id()));
+
+    canvas.save_ppm(&path, PpmFormat::Ascii).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(saved, canvas.to_ppm());
+}
+
+#[test]
+fn should_refuse_to_save_unknown_extensions() {
+    let canvas = Canvas::new(1, 1);
+
+    let error = canvas.save("image.bmp").unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn should_round_trip_an_8_bit_png() {
+    let mut canvas = Canvas::with_color(7, 3, Color::new(1.0, 0.8, 0.6));
+    canvas.set_pixel(2, 1, Color::new(-0.5, 0.5, 1.5));
+    let mut encoded = Vec::new();
+
+    canvas.encode_png(&mut encoded, PngDepth::Eight).unwrap();
+
+    let (width, height, depth, samples) = png::tests::decode(&encoded);
+    assert_eq!((width, height, depth), (7, 3, 8));
+    let expected: Vec<u8> = canvas
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_true_color())
+        .collect();
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn should_keep_precision_in_a_16_bit_png() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.set_pixel(0, 0, Color::new(0.5, 0.001, 1.0));
+    let mut encoded = Vec::new();
+
+    canvas.encode_png(&mut encoded, PngDepth::Sixteen).unwrap();
+
+    let (_, _, depth, samples) = png::tests::decode(&encoded);
+    assert_eq!(depth, 16);
+    assert_eq!(samples[..6], [0x80, 0x00, 0x00, 0x42, 0xFF, 0xFF]);
+    assert_eq!(samples[6..], [0; 6]);
+}
+
+#[test]
+fn should_save_png_by_extension() {
+    let canvas = Canvas::with_color(4, 4, Color::new(0.2, 0.4, 0.6));
+    let path = std::env::temp_dir().join(format!("canvas-save-{}.png", std::process::id()));
+
+    canvas.save(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut expected = Vec::new();
+    canvas.encode_png(&mut expected, PngDepth::Eight).unwrap();
+    assert_eq!(saved, expected);
+}
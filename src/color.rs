@@ -17,7 +17,7 @@ impl Color {
         self.r == 0.0 && self.g == 0.0 && self.b == 0.0
     }
 
-    pub fn to_true_color(&self) -> Vec<u8> {
+    pub fn to_true_color(self) -> Vec<u8> {
         vec![
             (self.r.clamp(0.0, 1.0) * 255.0) as u8,
             (self.g.clamp(0.0, 1.0) * 255.0) as u8,
@@ -0,0 +1,241 @@
+use crate::bounds::BoundingBox;
+use crate::cylinder::check_cap;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+use crate::{equal, EPSILON};
+
+/// A double-napped cone around the y axis, optionally truncated and capped.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    properties: ShapeProperties,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn new() -> Self {
+        Self {
+            properties: ShapeProperties::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Self::new()
+        }
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || equal(ray.direction.y, 0.0) {
+            return;
+        }
+
+        // The radius of a cone's cap is the absolute y value at that cap.
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if check_cap(ray, t, y.abs()) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Cone {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = vec![];
+        let (o, d) = (ray.origin, ray.direction);
+
+        let a = d.x.powi(2) - d.y.powi(2) + d.z.powi(2);
+        let b = 2.0 * o.x * d.x - 2.0 * o.y * d.y + 2.0 * o.z * d.z;
+        let c = o.x.powi(2) - o.y.powi(2) + o.z.powi(2);
+
+        if equal(a, 0.0) {
+            // The ray is parallel to one of the cone's halves.
+            if !equal(b, 0.0) {
+                let t = -c / (2.0 * b);
+                let y = o.y + t * d.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < -EPSILON {
+                return Intersections::default();
+            }
+            let discriminant = discriminant.max(0.0);
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+            for t in [t0, t1] {
+                let y = o.y + t * d.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections::new(xs)
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: &Intersection) -> Vector {
+        let distance = point.x.powi(2) + point.z.powi(2);
+
+        if distance < point.y.powi(2) && point.y >= self.maximum - EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if distance < point.y.powi(2) && point.y <= self.minimum + EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            let y = distance.sqrt();
+            let y = if point.y > 0.0 { -y } else { y };
+            Vector::new(point.x, y, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+        BoundingBox::new(
+            Point::new(-limit, self.minimum, -limit),
+            Point::new(limit, self.maximum, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_intersect_a_cone_with_a_ray() {
+        let cone = Cone::new();
+        let cases = [
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                5.0,
+                5.0,
+            ),
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(1.0, 1.0, 1.0),
+                8.66025,
+                8.66025,
+            ),
+            (
+                Point::new(1.0, 1.0, -5.0),
+                Vector::new(-0.5, -1.0, 1.0),
+                4.55006,
+                49.44994,
+            ),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            let xs = cone.local_intersect(&ray);
+
+            assert_eq!(xs.len(), 2);
+            assert!(equal(xs[0].t, t0));
+            assert!(equal(xs[1].t, t1));
+        }
+    }
+
+    #[test]
+    fn should_intersect_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+        let cone = Cone::new();
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 1.0, 1.0).normalize(),
+        );
+
+        let xs = cone.local_intersect(&ray);
+
+        assert_eq!(xs.len(), 1);
+        assert!(equal(xs[0].t, 0.35355));
+    }
+
+    #[test]
+    fn should_intersect_the_caps_of_a_closed_cone() {
+        let cone = Cone::truncated(-0.5, 0.5, true);
+        let cases = [
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0), 0),
+            (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 1.0), 2),
+            (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 0.0), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(cone.local_intersect(&ray).len(), count);
+        }
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_cone() {
+        let cone = Cone::new();
+        let cases = [
+            (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 0.0)),
+            (
+                Point::new(1.0, 1.0, 1.0),
+                Vector::new(1.0, -(2.0_f64.sqrt()), 1.0),
+            ),
+            (Point::new(-1.0, -1.0, 0.0), Vector::new(-1.0, 1.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(
+                cone.local_normal_at(point, &Intersection::new(0.0, &cone)),
+                normal
+            );
+        }
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_the_caps_of_a_cone() {
+        let cone = Cone::truncated(-1.0, 1.0, true);
+
+        assert_eq!(
+            cone.local_normal_at(Point::new(0.5, 1.0, 0.0), &Intersection::new(0.0, &cone)),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            cone.local_normal_at(Point::new(0.5, -1.0, 0.0), &Intersection::new(0.0, &cone)),
+            Vector::new(0.0, -1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let unbounded = Cone::new().bounds();
+        let bounded = Cone::truncated(-5.0, 3.0, false).bounds();
+
+        assert_eq!(unbounded.min.x, f64::NEG_INFINITY);
+        assert_eq!(unbounded.max.y, f64::INFINITY);
+        assert_eq!(bounded.min, Point::new(-5.0, -5.0, -5.0));
+        assert_eq!(bounded.max, Point::new(5.0, 3.0, 5.0));
+    }
+}
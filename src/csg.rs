@@ -0,0 +1,281 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Parent, Shape, ShapeProperties};
+use crate::stats;
+use crate::transform::InvertibleTransform;
+use crate::vector::Vector;
+use std::sync::Arc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+/// Constructive solid geometry: two shapes combined with a boolean operation.
+#[derive(Debug)]
+pub struct Csg {
+    properties: ShapeProperties,
+    pub operation: Operation,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    bounds: BoundingBox,
+}
+
+impl Csg {
+    pub fn new(
+        operation: Operation,
+        left: impl Shape + 'static,
+        right: impl Shape + 'static,
+    ) -> Self {
+        Self::from_boxed(operation, Box::new(left), Box::new(right))
+    }
+
+    pub fn from_boxed(operation: Operation, left: Box<dyn Shape>, right: Box<dyn Shape>) -> Self {
+        let mut bounds = left.parent_space_bounds();
+        bounds.merge(&right.parent_space_bounds());
+
+        let mut csg = Self {
+            properties: ShapeProperties::new(),
+            operation,
+            left,
+            right,
+            bounds,
+        };
+        csg.relink_children();
+        csg
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.left.as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.right.as_ref()
+    }
+
+    /// Keeps only the intersections that lie on the surface of the combined shape.
+    pub fn filter_intersections<'a>(&self, intersections: Intersections<'a>) -> Intersections<'a> {
+        let mut inl = false;
+        let mut inr = false;
+        let mut result = vec![];
+
+        for intersection in intersections {
+            let lhit = self.left.includes(intersection.object);
+
+            if intersection_allowed(self.operation, lhit, inl, inr) {
+                result.push(intersection);
+            }
+
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+
+        Intersections::new(result)
+    }
+
+    fn relink_children(&mut self) {
+        let parent = Arc::new(Parent {
+            transform: *self.invertible_transform(),
+            parent: self.parent().cloned(),
+        });
+        self.left.set_parent(Some(parent.clone()));
+        self.right.set_parent(Some(parent));
+    }
+}
+
+/// Decides whether a hit on the left (`lhit`) or right child is kept, given
+/// whether the ray is currently inside the left (`inl`) and right (`inr`) child.
+pub fn intersection_allowed(operation: Operation, lhit: bool, inl: bool, inr: bool) -> bool {
+    match operation {
+        Operation::Union => (lhit && !inr) || (!lhit && !inl),
+        Operation::Intersection => (lhit && inr) || (!lhit && inl),
+        Operation::Difference => (lhit && !inr) || (!lhit && inl),
+    }
+}
+
+impl Shape for Csg {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.properties.transform = transform;
+        self.relink_children();
+    }
+
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.properties.parent = parent;
+        self.relink_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other) || self.left.includes(other) || self.right.includes(other)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        self.left.divide(threshold);
+        self.right.divide(threshold);
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let local_ray = ray.transform(self.invertible_transform().inverse());
+
+        stats::record(|stats| stats.bounds_tests += 1);
+        if !self.bounds.intersects(&local_ray) {
+            return Intersections::default();
+        }
+
+        stats::record(|stats| stats.nodes_visited += 1);
+        self.local_intersect(&local_ray)
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut intersections = self.left.intersect(ray);
+        intersections.extend(self.right.intersect(ray));
+        self.filter_intersections(intersections)
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: &Intersection) -> Vector {
+        unreachable!("CSG shapes have no surface of their own, normals come from their children")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::matrix::Matrix;
+    use crate::sphere::Sphere;
+
+    #[test]
+    fn should_create_a_csg_shape() {
+        let csg = Csg::new(Operation::Union, Sphere::new(), Cube::new());
+
+        assert_eq!(csg.operation, Operation::Union);
+        assert!(csg.left().parent().is_some());
+        assert!(csg.right().parent().is_some());
+    }
+
+    #[test]
+    fn should_evaluate_the_rules_for_a_csg_operation() {
+        use Operation::*;
+        let cases = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+
+        for (operation, lhit, inl, inr, expected) in cases {
+            assert_eq!(intersection_allowed(operation, lhit, inl, inr), expected);
+        }
+    }
+
+    #[test]
+    fn should_filter_a_list_of_intersections() {
+        let cases = [
+            (Operation::Union, 0, 3),
+            (Operation::Intersection, 1, 2),
+            (Operation::Difference, 0, 1),
+        ];
+
+        for (operation, x0, x1) in cases {
+            let csg = Csg::new(operation, Sphere::new(), Cube::new());
+            let xs = [
+                Intersection::new(1.0, csg.left()),
+                Intersection::new(2.0, csg.right()),
+                Intersection::new(3.0, csg.left()),
+                Intersection::new(4.0, csg.right()),
+            ];
+
+            let result = csg.filter_intersections(Intersections::new(xs.to_vec()));
+
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0], xs[x0]);
+            assert_eq!(result[1], xs[x1]);
+        }
+    }
+
+    #[test]
+    fn should_have_a_bounding_box_that_contains_its_children() {
+        let mut right = Sphere::new();
+        right.set_transform(Matrix::translation(2.0, 3.0, 4.0));
+        let csg = Csg::new(Operation::Difference, Sphere::new(), right);
+
+        let bounds = csg.bounds();
+
+        assert_eq!(bounds.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Point::new(3.0, 4.0, 5.0));
+    }
+
+    #[test]
+    fn should_miss_a_csg_object() {
+        let csg = Csg::new(Operation::Union, Sphere::new(), Cube::new());
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(csg.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_hit_a_csg_object() {
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix::translation(0.0, 0.0, 0.5));
+        let csg = Csg::new(Operation::Union, Sphere::new(), s2);
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = csg.local_intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert!(std::ptr::addr_eq(xs[0].object, csg.left()));
+        assert_eq!(xs[1].t, 6.5);
+        assert!(std::ptr::addr_eq(xs[1].object, csg.right()));
+    }
+
+    #[test]
+    fn should_drill_a_hole_with_a_difference() {
+        let mut drill = Sphere::new();
+        drill.set_transform(Matrix::scaling(0.5, 0.5, 2.0));
+        let csg = Csg::new(Operation::Difference, Cube::new(), drill);
+        let through_hole = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let through_plate = Ray::new(Point::new(0.8, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(csg.intersect(&through_hole).is_empty());
+        assert_eq!(csg.intersect(&through_plate).len(), 2);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+use crate::EPSILON;
+
+/// An axis-aligned cube spanning -1 to 1 on every axis in object space.
+#[derive(Debug, Clone, Default)]
+pub struct Cube {
+    properties: ShapeProperties,
+}
+
+impl Cube {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Returns where a ray enters and leaves the slab between -1 and 1 on one axis.
+pub fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    let tmin_numerator = -1.0 - origin;
+    let tmax_numerator = 1.0 - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Intersections::default();
+        }
+
+        Intersections::new(vec![
+            Intersection::new(tmin, self),
+            Intersection::new(tmax, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: &Intersection) -> Vector {
+        let maxc = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+        if maxc == point.x.abs() {
+            Vector::new(point.x, 0.0, 0.0)
+        } else if maxc == point.y.abs() {
+            Vector::new(0.0, point.y, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_intersect_a_cube_from_every_side() {
+        let cube = Cube::new();
+        let cases = [
+            (
+                Point::new(5.0, 0.5, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(-5.0, 0.5, 0.0),
+                Vector::new(1.0, 0.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 5.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, -5.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, 5.0),
+                Vector::new(0.0, 0.0, -1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.0, 0.5, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                -1.0,
+                1.0,
+            ),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let xs = cube.local_intersect(&Ray::new(origin, direction));
+
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0].t, t1);
+            assert_eq!(xs[1].t, t2);
+        }
+    }
+
+    #[test]
+    fn should_miss_a_cube() {
+        let cube = Cube::new();
+        let cases = [
+            (
+                Point::new(-2.0, 0.0, 0.0),
+                Vector::new(0.2673, 0.5345, 0.8018),
+            ),
+            (
+                Point::new(0.0, -2.0, 0.0),
+                Vector::new(0.8018, 0.2673, 0.5345),
+            ),
+            (
+                Point::new(0.0, 0.0, -2.0),
+                Vector::new(0.5345, 0.8018, 0.2673),
+            ),
+            (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(2.0, 2.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+
+        for (origin, direction) in cases {
+            assert!(cube
+                .local_intersect(&Ray::new(origin, direction))
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_the_surface_of_a_cube() {
+        let cube = Cube::new();
+        let cases = [
+            (Point::new(1.0, 0.5, -0.8), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -0.2, 0.9), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(-0.4, 1.0, -0.1), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.3, -1.0, -0.7), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(-0.6, 0.3, 1.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(0.4, 0.4, -1.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(1.0, 1.0, 1.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -1.0, -1.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(
+                cube.local_normal_at(point, &Intersection::new(0.0, &cube)),
+                normal
+            );
+        }
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let bounds = Cube::new().bounds();
+
+        assert_eq!(bounds.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Point::new(1.0, 1.0, 1.0));
+    }
+}
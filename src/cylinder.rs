@@ -0,0 +1,266 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+use crate::{equal, EPSILON};
+
+/// A cylinder of radius 1 around the y axis, optionally truncated and capped.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    properties: ShapeProperties,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn new() -> Self {
+        Self {
+            properties: ShapeProperties::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Self {
+            minimum,
+            maximum,
+            closed,
+            ..Self::new()
+        }
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || equal(ray.direction.y, 0.0) {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if check_cap(ray, t, 1.0) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks whether the intersection at `t` lies within `radius` of the y axis.
+pub fn check_cap(ray: &Ray, t: f64, radius: f64) -> bool {
+    let x = ray.origin.x + t * ray.direction.x;
+    let z = ray.origin.z + t * ray.direction.z;
+    x.powi(2) + z.powi(2) <= radius.powi(2) + EPSILON
+}
+
+impl Shape for Cylinder {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut xs = vec![];
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+
+        if !equal(a, 0.0) {
+            let b = 2.0 * ray.origin.x * ray.direction.x + 2.0 * ray.origin.z * ray.direction.z;
+            let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - 1.0;
+            let discriminant = b * b - 4.0 * a * c;
+
+            if discriminant < 0.0 {
+                return Intersections::default();
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+            for t in [t0, t1] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections::new(xs)
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: &Intersection) -> Vector {
+        let distance = point.x.powi(2) + point.z.powi(2);
+
+        if distance < 1.0 && point.y >= self.maximum - EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if distance < 1.0 && point.y <= self.minimum + EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            Vector::new(point.x, 0.0, point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_miss_a_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(1.0, 1.0, 1.0)),
+        ];
+
+        for (origin, direction) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert!(cylinder.local_intersect(&ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn should_strike_a_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            (
+                Point::new(1.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                5.0,
+                5.0,
+            ),
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, -5.0),
+                Vector::new(0.1, 1.0, 1.0),
+                6.80798,
+                7.08872,
+            ),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            let xs = cylinder.local_intersect(&ray);
+
+            assert_eq!(xs.len(), 2);
+            assert!(equal(xs[0].t, t0));
+            assert!(equal(xs[1].t, t1));
+        }
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_cylinder() {
+        let cylinder = Cylinder::new();
+        let cases = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(0.0, 5.0, -1.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, -2.0, 1.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(-1.0, 1.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(
+                cylinder.local_normal_at(point, &Intersection::new(0.0, &cylinder)),
+                normal
+            );
+        }
+    }
+
+    #[test]
+    fn should_be_infinite_and_open_by_default() {
+        let cylinder = Cylinder::new();
+
+        assert_eq!(cylinder.minimum, f64::NEG_INFINITY);
+        assert_eq!(cylinder.maximum, f64::INFINITY);
+        assert!(!cylinder.closed);
+    }
+
+    #[test]
+    fn should_intersect_a_constrained_cylinder() {
+        let cylinder = Cylinder::truncated(1.0, 2.0, false);
+        let cases = [
+            (Point::new(0.0, 1.5, 0.0), Vector::new(0.1, 1.0, 0.0), 0),
+            (Point::new(0.0, 3.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.5, -2.0), Vector::new(0.0, 0.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(cylinder.local_intersect(&ray).len(), count);
+        }
+    }
+
+    #[test]
+    fn should_intersect_the_caps_of_a_closed_cylinder() {
+        let cylinder = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            (Point::new(0.0, 3.0, 0.0), Vector::new(0.0, -1.0, 0.0), 2),
+            (Point::new(0.0, 3.0, -2.0), Vector::new(0.0, -1.0, 2.0), 2),
+            (Point::new(0.0, 4.0, -2.0), Vector::new(0.0, -1.0, 1.0), 2),
+            (Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 1.0, 2.0), 2),
+            (Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(cylinder.local_intersect(&ray).len(), count);
+        }
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_the_end_caps() {
+        let cylinder = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            (Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.0, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.0, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.5, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 2.0, 0.5), Vector::new(0.0, 1.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(
+                cylinder.local_normal_at(point, &Intersection::new(0.0, &cylinder)),
+                normal
+            );
+        }
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let unbounded = Cylinder::new().bounds();
+        let bounded = Cylinder::truncated(-5.0, 3.0, false).bounds();
+
+        assert_eq!(unbounded.min.y, f64::NEG_INFINITY);
+        assert_eq!(unbounded.max.y, f64::INFINITY);
+        assert_eq!(bounded.min, Point::new(-1.0, -5.0, -1.0));
+        assert_eq!(bounded.max, Point::new(1.0, 3.0, 1.0));
+    }
+}
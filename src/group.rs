@@ -0,0 +1,447 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Parent, Shape, ShapeProperties};
+use crate::stats;
+use crate::transform::InvertibleTransform;
+use crate::vector::Vector;
+use std::sync::Arc;
+
+type Children = Vec<Box<dyn Shape>>;
+
+/// A collection of shapes that are transformed together.
+///
+/// Groups double as the nodes of the bounding volume hierarchy: rays that
+/// miss a group's bounding box never reach its children.
+#[derive(Debug)]
+pub struct Group {
+    properties: ShapeProperties,
+    children: Children,
+    bounds: BoundingBox,
+}
+
+impl Group {
+    pub fn new() -> Self {
+        Self {
+            properties: ShapeProperties::new(),
+            children: vec![],
+            bounds: BoundingBox::empty(),
+        }
+    }
+
+    fn with_children(children: Children) -> Self {
+        let mut group = Self::new();
+        for child in children {
+            group.add_boxed_child(child);
+        }
+        group
+    }
+
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    pub fn add_child(&mut self, child: impl Shape + 'static) {
+        self.add_boxed_child(Box::new(child));
+    }
+
+    pub fn add_boxed_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_parent(Some(self.as_parent()));
+        self.bounds.merge(&child.parent_space_bounds());
+        self.children.push(child);
+    }
+
+    /// Splits the children at the median of their centers along the axis
+    /// they are most spread out on. Unbounded children, such as planes, stay
+    /// in this group.
+    fn partition_children(&mut self) -> (Children, Children) {
+        let (mut bounded, unbounded): (Vec<_>, Vec<_>) = std::mem::take(&mut self.children)
+            .into_iter()
+            .map(|child| (child.parent_space_bounds(), child))
+            .partition(|(bounds, _)| bounds.is_finite());
+        self.children = unbounded.into_iter().map(|(_, child)| child).collect();
+
+        let mut centroids = BoundingBox::empty();
+        for (bounds, _) in &bounded {
+            centroids.add_point(bounds.centroid());
+        }
+        let extent = centroids.max - centroids.min;
+        let axis = |point: Point| {
+            if extent.x >= extent.y && extent.x >= extent.z {
+                point.x
+            } else if extent.y >= extent.z {
+                point.y
+            } else {
+                point.z
+            }
+        };
+
+        bounded.sort_by(|(a, _), (b, _)| axis(a.centroid()).total_cmp(&axis(b.centroid())));
+        let right = bounded.split_off(bounded.len() / 2);
+
+        (
+            bounded.into_iter().map(|(_, child)| child).collect(),
+            right.into_iter().map(|(_, child)| child).collect(),
+        )
+    }
+
+    fn as_parent(&self) -> Arc<Parent> {
+        Arc::new(Parent {
+            transform: *self.invertible_transform(),
+            parent: self.parent().cloned(),
+        })
+    }
+
+    /// Children hold a snapshot of this group's transforms, so refresh it on every change.
+    fn relink_children(&mut self) {
+        let parent = self.as_parent();
+        for child in self.children.iter_mut() {
+            child.set_parent(Some(parent.clone()));
+        }
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Group {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.properties.transform = transform;
+        self.relink_children();
+    }
+
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.properties.parent = parent;
+        self.relink_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other) || self.children.iter().any(|child| child.includes(other))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        if self.children.len() > threshold {
+            let (left, right) = self.partition_children();
+            for part in [left, right] {
+                match part.len() {
+                    0 => {}
+                    1 => self.children.extend(part),
+                    _ => self.add_child(Group::with_children(part)),
+                }
+            }
+        }
+
+        for child in self.children.iter_mut() {
+            child.divide(threshold);
+        }
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let local_ray = ray.transform(self.invertible_transform().inverse());
+
+        stats::record(|stats| stats.bounds_tests += 1);
+        if !self.bounds.intersects(&local_ray) {
+            return Intersections::default();
+        }
+
+        stats::record(|stats| stats.nodes_visited += 1);
+        self.local_intersect(&local_ray)
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut intersections = Intersections::default();
+        for child in &self.children {
+            intersections.extend(child.intersect(ray));
+        }
+        intersections
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: &Intersection) -> Vector {
+        unreachable!("groups have no surface of their own, normals come from their children")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cylinder::Cylinder;
+    use crate::matrix::Matrix;
+    use crate::plane::Plane;
+    use crate::shape::tests::TestShape;
+    use crate::sphere::Sphere;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_create_an_empty_group() {
+        let group = Group::new();
+
+        assert_eq!(*group.transform(), Matrix::identity());
+        assert!(group.children().is_empty());
+    }
+
+    #[test]
+    fn should_add_a_child_to_a_group() {
+        let mut group = Group::new();
+        group.set_transform(Matrix::translation(1.0, 2.0, 3.0));
+
+        group.add_child(TestShape::default());
+
+        assert_eq!(group.children().len(), 1);
+        let parent = group.children()[0].parent().unwrap();
+        assert_eq!(
+            *parent.transform.matrix(),
+            Matrix::translation(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn should_not_intersect_an_empty_group() {
+        let group = Group::new();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(group.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_intersect_a_nonempty_group() {
+        let mut group = Group::new();
+        group.add_child(Sphere::new());
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix::translation(0.0, 0.0, -3.0));
+        group.add_child(s2);
+        let mut s3 = Sphere::new();
+        s3.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        group.add_child(s3);
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = group.local_intersect(&ray);
+
+        let s1 = group.children()[0].as_ref();
+        let s2 = group.children()[1].as_ref();
+        assert_eq!(xs.len(), 4);
+        assert!(std::ptr::addr_eq(xs[0].object, s2));
+        assert!(std::ptr::addr_eq(xs[1].object, s2));
+        assert!(std::ptr::addr_eq(xs[2].object, s1));
+        assert!(std::ptr::addr_eq(xs[3].object, s1));
+    }
+
+    #[test]
+    fn should_intersect_a_transformed_group() {
+        let mut group = Group::new();
+        group.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        group.add_child(sphere);
+        let ray = Ray::new(Point::new(10.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_eq!(group.intersect(&ray).len(), 2);
+    }
+
+    #[test]
+    fn should_convert_a_point_from_world_to_object_space() {
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        g2.set_parent(Some(g1.as_parent()));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        sphere.set_parent(Some(g2.as_parent()));
+
+        let p = sphere.world_to_object(Point::new(-2.0, 0.0, -10.0));
+
+        assert_eq!(p, Point::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_convert_a_normal_from_object_to_world_space() {
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix::scaling(1.0, 2.0, 3.0));
+        g2.set_parent(Some(g1.as_parent()));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        sphere.set_parent(Some(g2.as_parent()));
+        let a = 3.0_f64.sqrt() / 3.0;
+
+        let n = sphere.normal_to_world(Vector::new(a, a, a));
+
+        assert_eq!(n, Vector::new(0.28571, 0.42857, -0.85714));
+    }
+
+    #[test]
+    fn should_find_the_normal_on_a_child_object() {
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix::scaling(1.0, 2.0, 3.0));
+        g2.set_parent(Some(g1.as_parent()));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        sphere.set_parent(Some(g2.as_parent()));
+        let hit = Intersection::new(0.0, &sphere);
+
+        let n = sphere.normal_at(Point::new(1.7321, 1.1547, -5.5774), &hit);
+
+        assert_eq!(n, Vector::new(0.2857, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn should_have_a_bounding_box_that_contains_its_children() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(2.0, 5.0, -3.0) * Matrix::scaling(2.0, 2.0, 2.0));
+        let mut cylinder = Cylinder::truncated(-2.0, 2.0, false);
+        cylinder
+            .set_transform(Matrix::translation(-4.0, -1.0, 4.0) * Matrix::scaling(0.5, 1.0, 0.5));
+        let mut group = Group::new();
+        group.add_child(sphere);
+        group.add_child(cylinder);
+
+        let bounds = group.bounds();
+
+        assert_eq!(bounds.min, Point::new(-4.5, -3.0, -5.0));
+        assert_eq!(bounds.max, Point::new(4.0, 7.0, 4.5));
+    }
+
+    #[test]
+    fn should_skip_children_when_the_ray_misses_the_bounds() {
+        let mut group = Group::new();
+        group.add_child(Sphere::new());
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+        stats::take();
+
+        let xs = group.intersect(&ray);
+
+        assert!(xs.is_empty());
+        let counted = stats::take();
+        assert_eq!(counted.bounds_tests, 1);
+        assert_eq!(counted.nodes_visited, 0);
+        assert_eq!(counted.primitive_tests, 0);
+    }
+
+    #[test]
+    fn should_divide_a_group_into_subgroups() {
+        let mut group = Group::new();
+        for x in [-2.0, 2.0, -3.0, 3.0] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(Matrix::translation(x, 0.0, 0.0));
+            group.add_child(sphere);
+        }
+
+        group.divide(1);
+
+        assert_eq!(group.children().len(), 2);
+        let left = group.children()[0].parent_space_bounds();
+        let right = group.children()[1].parent_space_bounds();
+        assert_eq!(left.min.x, -4.0);
+        assert_eq!(left.max.x, -1.0);
+        assert_eq!(right.min.x, 1.0);
+        assert_eq!(right.max.x, 4.0);
+    }
+
+    #[test]
+    fn should_keep_unbounded_children_in_place_when_dividing() {
+        let mut group = Group::new();
+        group.add_child(Plane::new());
+        for x in [-2.0, 2.0, 4.0] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(Matrix::translation(x, 0.0, 0.0));
+            group.add_child(sphere);
+        }
+
+        group.divide(2);
+
+        assert_eq!(group.children().len(), 3);
+        assert!(!group.children()[0].parent_space_bounds().is_finite());
+    }
+
+    #[test]
+    fn should_find_the_same_hits_with_fewer_tests_after_dividing() {
+        let build = || {
+            let mut group = Group::new();
+            for i in 0..64 {
+                let mut sphere = Sphere::new();
+                sphere.set_transform(Matrix::translation(
+                    (i % 8) as f64 * 3.0,
+                    (i / 8) as f64 * 3.0,
+                    0.0,
+                ));
+                group.add_child(sphere);
+            }
+            group
+        };
+        let flat = build();
+        let mut divided = build();
+        divided.divide(4);
+        let ray = Ray::new(Point::new(9.0, 12.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        stats::take();
+        let flat_ts: Vec<f64> = flat.intersect(&ray).iter().map(|i| i.t).collect();
+        let flat_stats = stats::take();
+        let divided_ts: Vec<f64> = divided.intersect(&ray).iter().map(|i| i.t).collect();
+        let divided_stats = stats::take();
+
+        assert_eq!(flat_ts, vec![4.0, 6.0]);
+        assert_eq!(flat_ts, divided_ts);
+        assert_eq!(flat_stats.primitive_tests, 64);
+        assert!(divided_stats.primitive_tests < 8);
+    }
+
+    #[test]
+    fn should_include_its_descendants() {
+        let mut inner = Group::new();
+        inner.add_child(Sphere::new());
+        let mut outer = Group::new();
+        outer.add_child(inner);
+        let other = Sphere::new();
+
+        let child = outer.children()[0].as_ref();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = outer.intersect(&ray);
+
+        assert!(outer.includes(&outer));
+        assert!(outer.includes(child));
+        assert!(outer.includes(xs[0].object));
+        assert!(!outer.includes(&other));
+    }
+
+    #[test]
+    fn should_propagate_transforms_to_nested_children() {
+        let mut inner = Group::new();
+        inner.set_transform(Matrix::scaling(1.0, 2.0, 3.0));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+        inner.add_child(sphere);
+        let mut outer = Group::new();
+        outer.add_child(inner);
+
+        // Transforming the outer group after the fact still reaches the sphere.
+        outer.set_transform(Matrix::rotation_y(PI / 2.0));
+        let ray = Ray::new(Point::new(0.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = outer.intersect(&ray);
+        let hit = xs.hit().unwrap();
+
+        let n = hit.object.normal_at(ray.position(hit.t), hit);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(n, Vector::new(0.0, 0.0, -1.0));
+    }
+}
@@ -0,0 +1,454 @@
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::Shape;
+use crate::vector::Vector;
+use crate::{equal, EPSILON};
+use std::ops;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Intersection<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    /// Barycentric coordinates of the hit, only meaningful for triangles.
+    pub u: f64,
+    pub v: f64,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a dyn Shape) -> Self {
+        Self::with_uv(t, object, 0.0, 0.0)
+    }
+
+    pub fn with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Self {
+        Self { t, object, u, v }
+    }
+
+    /// Precomputes shading state for this hit, `xs` being every intersection along `ray`.
+    pub fn prepare_computations(&self, ray: &Ray, xs: &Intersections<'a>) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at(point, self);
+
+        let inside = normalv.dot(&eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+        let (n1, n2) = self.refractive_indices(xs);
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            eyev,
+            normalv,
+            inside,
+            over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            reflectv: ray.direction.reflect(&normalv),
+            n1,
+            n2,
+        }
+    }
+
+    /// The refractive indices of the media on either side of this hit, found by
+    /// tracking which objects the ray is inside of as it passes through `xs`.
+    fn refractive_indices(&self, xs: &Intersections<'a>) -> (f64, f64) {
+        let refractive_index = |containers: &[&dyn Shape]| {
+            containers
+                .last()
+                .map_or(1.0, |object| object.material().refractive_index)
+        };
+
+        let mut containers: Vec<&dyn Shape> = Vec::new();
+        let mut n1 = 1.0;
+        for i in xs.iter() {
+            if i == self {
+                n1 = refractive_index(&containers);
+            }
+
+            match containers
+                .iter()
+                .position(|object| std::ptr::addr_eq(*object, i.object))
+            {
+                Some(index) => {
+                    containers.remove(index);
+                }
+                None => containers.push(i.object),
+            }
+
+            if i == self {
+                return (n1, refractive_index(&containers));
+            }
+        }
+
+        (n1, refractive_index(&containers))
+    }
+}
+
+/// Precomputed state about an intersection, reused while shading the hit.
+#[derive(Debug, Copy, Clone)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Point,
+    pub eyev: Vector,
+    pub normalv: Vector,
+    pub inside: bool,
+    /// `point` nudged along the normal, to keep shadow rays from hitting the surface itself.
+    pub over_point: Point,
+    /// `point` nudged below the surface, where refracted rays start.
+    pub under_point: Point,
+    pub reflectv: Vector,
+    /// Refractive index of the material being exited.
+    pub n1: f64,
+    /// Refractive index of the material being entered.
+    pub n2: f64,
+}
+
+impl Computations<'_> {
+    /// Schlick's approximation of the Fresnel effect: the fraction of light reflected.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(&self.normalv);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+}
+
+impl PartialEq for Intersection<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self.t, other.t) && std::ptr::addr_eq(self.object, other.object)
+    }
+}
+
+/// A list of intersections that is always kept sorted by ascending `t`.
+#[derive(Debug, Clone, Default)]
+pub struct Intersections<'a> {
+    intersections: Vec<Intersection<'a>>,
+}
+
+impl<'a> Intersections<'a> {
+    pub fn new(mut intersections: Vec<Intersection<'a>>) -> Self {
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+        Self { intersections }
+    }
+
+    pub fn push(&mut self, intersection: Intersection<'a>) {
+        let index = self
+            .intersections
+            .partition_point(|existing| existing.t <= intersection.t);
+        self.intersections.insert(index, intersection);
+    }
+
+    pub fn extend(&mut self, other: Intersections<'a>) {
+        self.intersections.extend(other.intersections);
+        self.intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+    }
+
+    /// The visible intersection, i.e. the one with the lowest non-negative `t`.
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
+        self.intersections.iter().find(|i| i.t >= 0.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.intersections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intersections.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Intersection<'a>> {
+        self.intersections.iter()
+    }
+}
+
+impl<'a> ops::Index<usize> for Intersections<'a> {
+    type Output = Intersection<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.intersections[index]
+    }
+}
+
+impl<'a> IntoIterator for Intersections<'a> {
+    type Item = Intersection<'a>;
+    type IntoIter = std::vec::IntoIter<Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intersections.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::matrix::Matrix;
+    use crate::plane::Plane;
+    use crate::shape::Shape;
+    use crate::sphere::Sphere;
+    use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+    fn glass_sphere() -> Sphere {
+        let mut sphere = Sphere::new();
+        let mut material = Material::new();
+        material.transparency = 1.0;
+        material.refractive_index = 1.5;
+        sphere.set_material(material);
+        sphere
+    }
+
+    fn glass_sphere_with(transform: Matrix<4>, refractive_index: f64) -> Sphere {
+        let mut sphere = glass_sphere();
+        sphere.set_transform(transform);
+        let mut material = sphere.material().clone();
+        material.refractive_index = refractive_index;
+        sphere.set_material(material);
+        sphere
+    }
+
+    #[test]
+    fn should_encapsulate_t_and_object() {
+        let sphere = Sphere::new();
+        let i = Intersection::new(3.5, &sphere);
+
+        assert_eq!(i.t, 3.5);
+        assert!(std::ptr::addr_eq(i.object, &sphere));
+    }
+
+    #[test]
+    fn should_aggregate_intersections() {
+        let sphere = Sphere::new();
+        let i1 = Intersection::new(1.0, &sphere);
+        let i2 = Intersection::new(2.0, &sphere);
+
+        let xs = Intersections::new(vec![i1, i2]);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 1.0);
+        assert_eq!(xs[1].t, 2.0);
+    }
+
+    #[test]
+    fn should_keep_intersections_sorted() {
+        let sphere = Sphere::new();
+        let mut xs = Intersections::new(vec![
+            Intersection::new(5.0, &sphere),
+            Intersection::new(-1.0, &sphere),
+        ]);
+        xs.push(Intersection::new(2.0, &sphere));
+        xs.extend(Intersections::new(vec![Intersection::new(0.5, &sphere)]));
+
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![-1.0, 0.5, 2.0, 5.0]);
+    }
+
+    #[test]
+    fn should_hit_when_all_intersections_have_positive_t() {
+        let sphere = Sphere::new();
+        let i1 = Intersection::new(1.0, &sphere);
+        let i2 = Intersection::new(2.0, &sphere);
+
+        let xs = Intersections::new(vec![i2, i1]);
+
+        assert_eq!(xs.hit(), Some(&i1));
+    }
+
+    #[test]
+    fn should_hit_when_some_intersections_have_negative_t() {
+        let sphere = Sphere::new();
+        let i1 = Intersection::new(-1.0, &sphere);
+        let i2 = Intersection::new(1.0, &sphere);
+
+        let xs = Intersections::new(vec![i2, i1]);
+
+        assert_eq!(xs.hit(), Some(&i2));
+    }
+
+    #[test]
+    fn should_not_hit_when_all_intersections_have_negative_t() {
+        let sphere = Sphere::new();
+        let i1 = Intersection::new(-2.0, &sphere);
+        let i2 = Intersection::new(-1.0, &sphere);
+
+        let xs = Intersections::new(vec![i2, i1]);
+
+        assert_eq!(xs.hit(), None);
+    }
+
+    #[test]
+    fn should_always_hit_the_lowest_nonnegative_intersection() {
+        let sphere = Sphere::new();
+        let i1 = Intersection::new(5.0, &sphere);
+        let i2 = Intersection::new(7.0, &sphere);
+        let i3 = Intersection::new(-3.0, &sphere);
+        let i4 = Intersection::new(2.0, &sphere);
+
+        let xs = Intersections::new(vec![i1, i2, i3, i4]);
+
+        assert_eq!(xs.hit(), Some(&i4));
+    }
+
+    #[test]
+    fn should_precompute_the_state_of_an_intersection() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = Sphere::new();
+        let i = Intersection::new(4.0, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(comps.t, i.t);
+        assert!(std::ptr::addr_eq(comps.object, &shape));
+        assert_eq!(comps.point, Point::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_flag_a_hit_on_the_outside() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = Sphere::new();
+        let i = Intersection::new(4.0, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn should_flag_a_hit_on_the_inside() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = Sphere::new();
+        let i = Intersection::new(1.0, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert!(comps.inside);
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_offset_the_point_along_the_normal() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix::translation(0.0, 0.0, 1.0));
+        let i = Intersection::new(5.0, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert!(comps.over_point.z < -EPSILON / 2.0);
+        assert!(comps.point.z > comps.over_point.z);
+    }
+
+    #[test]
+    fn should_precompute_the_reflection_vector() {
+        let shape = Plane::new();
+        let ray = Ray::new(
+            Point::new(0.0, 1.0, -1.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            comps.reflectv,
+            Vector::new(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2)
+        );
+    }
+
+    #[test]
+    fn should_find_n1_and_n2_at_various_intersections() {
+        let a = glass_sphere_with(Matrix::scaling(2.0, 2.0, 2.0), 1.5);
+        let b = glass_sphere_with(Matrix::translation(0.0, 0.0, -0.25), 2.0);
+        let c = glass_sphere_with(Matrix::translation(0.0, 0.0, 0.25), 2.5);
+        let ray = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ]);
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+
+        for (index, (n1, n2)) in expected.into_iter().enumerate() {
+            let comps = xs[index].prepare_computations(&ray, &xs);
+            assert_eq!((comps.n1, comps.n2), (n1, n2), "intersection {index}");
+        }
+    }
+
+    #[test]
+    fn should_offset_the_under_point_below_the_surface() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = glass_sphere_with(Matrix::translation(0.0, 0.0, 1.0), 1.5);
+        let i = Intersection::new(5.0, &shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert!(comps.under_point.z > EPSILON / 2.0);
+        assert!(comps.point.z < comps.under_point.z);
+    }
+
+    #[test]
+    fn should_totally_reflect_under_total_internal_reflection() {
+        let shape = glass_sphere();
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, FRAC_1_SQRT_2),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let xs = Intersections::new(vec![
+            Intersection::new(-FRAC_1_SQRT_2, &shape),
+            Intersection::new(FRAC_1_SQRT_2, &shape),
+        ]);
+
+        let comps = xs[1].prepare_computations(&ray, &xs);
+
+        assert_eq!(comps.schlick(), 1.0);
+    }
+
+    #[test]
+    fn should_barely_reflect_with_a_perpendicular_viewing_angle() {
+        let shape = glass_sphere();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(-1.0, &shape),
+            Intersection::new(1.0, &shape),
+        ]);
+
+        let comps = xs[1].prepare_computations(&ray, &xs);
+
+        assert!(equal(comps.schlick(), 0.04));
+    }
+
+    #[test]
+    fn should_strongly_reflect_with_a_small_angle_and_n2_greater_than_n1() {
+        let shape = glass_sphere();
+        let ray = Ray::new(Point::new(0.0, 0.99, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![Intersection::new(1.8589, &shape)]);
+
+        let comps = xs[0].prepare_computations(&ray, &xs);
+
+        assert!(equal(comps.schlick(), 0.48873));
+    }
+}
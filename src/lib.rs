@@ -1,17 +1,43 @@
 #![allow(dead_code, unused_must_use)]
 extern crate core;
 
-mod canvas;
-mod color;
-mod matrix;
-mod point;
-mod vector;
+pub mod bounds;
+pub mod camera;
+pub mod canvas;
+pub mod color;
+pub mod cone;
+pub mod csg;
+pub mod cube;
+pub mod cylinder;
+pub mod group;
+pub mod intersection;
+pub mod light;
+pub mod material;
+pub mod matrix;
+pub mod obj_file;
+pub mod pattern;
+pub mod plane;
+pub mod png;
+pub mod point;
+pub mod ppm;
+pub mod ray;
+pub mod scene;
+pub mod shape;
+pub mod sphere;
+pub mod stats;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
+pub mod vector;
+pub mod world;
+pub mod yaml;
 
 use crate::point::Point;
 use crate::vector::Vector;
 
+pub const EPSILON: f64 = 0.00001;
+
 pub fn equal(f1: f64, f2: f64) -> bool {
-    const EPSILON: f64 = 0.00001;
     if (f1 - f2).abs() < EPSILON {
         return true;
     }
@@ -79,6 +105,7 @@ mod test {
         println!("Cannonball went {:#?} meters!", projectile.position.x);
         println!("Finished after {} ticks", i);
 
-        canvas.write_to_file().expect("Could not write to file")
+        let path = std::env::temp_dir().join("virtual-cannon.ppm");
+        canvas.save(path).expect("Could not write to file")
     }
 }
@@ -0,0 +1,199 @@
+use crate::color::Color;
+use crate::material::Material;
+use crate::point::Point;
+use crate::shape::Shape;
+use crate::vector::Vector;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+    /// Whether objects occlude this light. Fill lights usually don't.
+    pub casts_shadows: bool,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            casts_shadows: true,
+        }
+    }
+}
+
+/// Shades `point` using the Phong reflection model.
+pub fn lighting(
+    material: &Material,
+    object: &dyn Shape,
+    light: &PointLight,
+    point: Point,
+    eyev: Vector,
+    normalv: Vector,
+    in_shadow: bool,
+) -> Color {
+    let black = Color::new(0.0, 0.0, 0.0);
+
+    let color = match &material.pattern {
+        Some(pattern) => pattern.pattern_at_shape(object, point),
+        None => material.color,
+    };
+
+    let effective_color = color * light.intensity;
+    let lightv = (light.position - point).normalize();
+    let ambient = effective_color * material.ambient;
+    if in_shadow {
+        return ambient;
+    }
+
+    let light_dot_normal = lightv.dot(&normalv);
+    if light_dot_normal < 0.0 {
+        return ambient;
+    }
+
+    let diffuse = effective_color * material.diffuse * light_dot_normal;
+
+    let reflectv = (-lightv).reflect(&normalv);
+    let reflect_dot_eye = reflectv.dot(&eyev);
+    let specular = if reflect_dot_eye <= 0.0 {
+        black
+    } else {
+        let factor = reflect_dot_eye.powf(material.shininess);
+        light.intensity * material.specular * factor
+    };
+
+    ambient + diffuse + specular
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::StripePattern;
+    use crate::sphere::Sphere;
+    use std::sync::Arc;
+
+    fn setup() -> (Material, Point) {
+        (Material::new(), Point::new(0.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn should_have_a_position_and_intensity() {
+        let intensity = Color::new(1.0, 1.0, 1.0);
+        let position = Point::new(0.0, 0.0, 0.0);
+
+        let light = PointLight::new(position, intensity);
+
+        assert_eq!(light.position, position);
+        assert_eq!(light.intensity, intensity);
+    }
+
+    #[test]
+    fn should_light_with_the_eye_between_the_light_and_the_surface() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+        assert_eq!(actual, Color::new(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn should_light_with_the_eye_offset_45_degrees() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0));
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+        assert_eq!(actual, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn should_light_with_the_light_offset_45_degrees() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+        assert_eq!(actual, Color::new(0.7364, 0.7364, 0.7364));
+    }
+
+    #[test]
+    fn should_light_with_the_eye_in_the_path_of_the_reflection_vector() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, -(2.0_f64.sqrt() / 2.0), -(2.0_f64.sqrt() / 2.0));
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+        assert_eq!(actual, Color::new(1.6364, 1.6364, 1.6364));
+    }
+
+    #[test]
+    fn should_light_with_the_light_behind_the_surface() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, false);
+
+        assert_eq!(actual, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn should_light_with_the_surface_in_shadow() {
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let actual = lighting(&m, &Sphere::new(), &light, position, eyev, normalv, true);
+
+        assert_eq!(actual, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn should_light_with_a_pattern_applied() {
+        let mut m = Material::new();
+        m.pattern = Some(Arc::new(StripePattern::new(
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(0.0, 0.0, 0.0),
+        )));
+        m.ambient = 1.0;
+        m.diffuse = 0.0;
+        m.specular = 0.0;
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let object = Sphere::new();
+
+        let c1 = lighting(
+            &m,
+            &object,
+            &light,
+            Point::new(0.9, 0.0, 0.0),
+            eyev,
+            normalv,
+            false,
+        );
+        let c2 = lighting(
+            &m,
+            &object,
+            &light,
+            Point::new(1.1, 0.0, 0.0),
+            eyev,
+            normalv,
+            false,
+        );
+
+        assert_eq!(c1, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c2, Color::new(0.0, 0.0, 0.0));
+    }
+}
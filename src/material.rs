@@ -0,0 +1,80 @@
+use crate::color::Color;
+use crate::pattern::Pattern;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub color: Color,
+    /// Overrides `color` when set.
+    pub pattern: Option<Arc<dyn Pattern>>,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    /// 0.0 is not reflective at all, 1.0 is a perfect mirror.
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
+}
+
+impl Material {
+    pub fn new() -> Self {
+        Self {
+            color: Color::new(1.0, 1.0, 1.0),
+            pattern: None,
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        }
+    }
+}
+
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+
+        same_pattern
+            && self.color == other.color
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_a_default_material() {
+        let m = Material::new();
+
+        assert_eq!(m.color, Color::new(1.0, 1.0, 1.0));
+        assert!(m.pattern.is_none());
+        assert_eq!(m.ambient, 0.1);
+        assert_eq!(m.diffuse, 0.9);
+        assert_eq!(m.specular, 0.9);
+        assert_eq!(m.shininess, 200.0);
+        assert_eq!(m.reflective, 0.0);
+        assert_eq!(m.transparency, 0.0);
+        assert_eq!(m.refractive_index, 1.0);
+    }
+}
@@ -5,8 +5,13 @@ use std::ops;
 use crate::point::Point;
 use crate::vector::Vector;
 
+/// How small a determinant may get, relative to the largest determinant
+/// a matrix with the same row lengths could have, before the matrix is
+/// considered singular.
+const SINGULARITY_TOLERANCE: f64 = 1e-12;
+
 #[derive(Copy, Clone)]
-struct Matrix<const D: usize> {
+pub struct Matrix<const D: usize> {
     entries: [[f64; D]; D],
 }
 
@@ -16,161 +21,163 @@ impl<const D: usize> From<[[f64; D]; D]> for Matrix<D> {
     }
 }
 
+impl<const D: usize> Default for Matrix<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const D: usize> Matrix<D> {
-    fn new() -> Matrix<D> {
+    pub fn new() -> Matrix<D> {
         Matrix::from([[0.0; D]; D])
     }
 
-    fn transpose(&self) -> Self {
+    pub fn identity() -> Matrix<D> {
+        let mut matrix = Matrix::new();
+        for i in 0..D {
+            matrix[i][i] = 1.0;
+        }
+        matrix
+    }
+
+    pub fn transpose(&self) -> Self {
         let mut entries = [[0.0; D]; D];
-        for row in 0..D {
-            for col in 0..D {
-                entries[col][row] = self.entries[row][col]
+        for (row, values) in self.entries.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                entries[col][row] = *value
             }
         }
         Self { entries }
     }
-}
 
-impl Matrix<2> {
-    fn determinant(&self) -> f64 {
-        self[0][0] * self[1][1] - self[1][0] * self[0][1]
+    pub fn determinant(&self) -> f64 {
+        LuDecomposition::new(self).determinant()
     }
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
-    }
-}
 
-impl Matrix<3> {
-    fn determinant(&self) -> f64 {
-        let mut determinant = 0.0;
-        for (index, element) in self[0].iter().enumerate() {
-            determinant += element * self.cofactor(0, index);
-        }
-        determinant
+    pub fn is_invertible(&self) -> bool {
+        !self.is_singular(self.determinant())
     }
 
-    fn submatrix(&self, row: usize, col: usize) -> Matrix<2> {
-        let mut submatrix = Matrix::new();
-        let mut i = 0;
-        let mut j = 0;
-
-        //TODO: Can I write self.iter() here?
-        for (index, element) in self.entries.iter().enumerate() {
-            if index == row {
-                continue;
-            }
-            for (index, entry) in element.iter().enumerate() {
-                if index == col {
-                    continue;
-                }
-
-                submatrix[i][j] = *entry;
-
-                j = match j {
-                    1 => 0,
-                    _ => 1,
-                };
-            }
-
-            i = match i {
-                1 => 0,
-                _ => 1,
-            };
-        }
-        submatrix
+    /// Whether `determinant` is too close to zero for this matrix to be
+    /// inverted reliably.
+    ///
+    /// Hadamard's inequality bounds the determinant by the product of the
+    /// row lengths, so comparing against that bound doesn't depend on the
+    /// matrix's overall scale: a tiny uniform scale is still invertible.
+    fn is_singular(&self, determinant: f64) -> bool {
+        let bound: f64 = self
+            .entries
+            .iter()
+            .map(|row| row.iter().map(|entry| entry * entry).sum::<f64>().sqrt())
+            .product();
+        !determinant.is_finite() || determinant.abs() <= SINGULARITY_TOLERANCE * bound
     }
 
-    fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
+    /// Panics if the matrix isn't invertible, see `try_inverse`.
+    pub fn inverse(&self) -> Self {
+        self.try_inverse()
+            .unwrap_or_else(|| panic!("matrix is not invertible: {:?}", self))
     }
 
-    fn cofactor(&self, row: usize, col: usize) -> f64 {
-        let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
-            minor
-        } else {
-            -minor
+    /// The inverse, or `None` if the matrix is singular or nearly so.
+    pub fn try_inverse(&self) -> Option<Self> {
+        let lu = LuDecomposition::new(self);
+        if self.is_singular(lu.determinant()) {
+            return None;
         }
-    }
 
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
-    }
-}
-
-impl Matrix<4> {
-    fn determinant(&self) -> f64 {
-        let mut determinant = 0.0;
-        for (index, element) in self[0].iter().enumerate() {
-            determinant += element * self.cofactor(0, index);
+        // Each column of the inverse solves `self * x` = that column of the identity.
+        let mut inverse = Matrix::new();
+        for col in 0..D {
+            let mut unit = [0.0; D];
+            unit[col] = 1.0;
+            for (row, value) in lu.solve(unit).into_iter().enumerate() {
+                inverse[row][col] = value;
+            }
         }
-        determinant
+        Some(inverse)
     }
 
-    fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
-    }
-
-    fn cofactor(&self, row: usize, col: usize) -> f64 {
-        let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
-            minor
-        } else {
-            -minor
+    /// The `x` for which `self * x == b`, or `None` if the matrix is singular
+    /// or nearly so.
+    pub fn solve(&self, b: [f64; D]) -> Option<[f64; D]> {
+        let lu = LuDecomposition::new(self);
+        if self.is_singular(lu.determinant()) {
+            return None;
         }
+        Some(lu.solve(b))
     }
+}
+
+/// A matrix factored into `L * U` after reordering its rows, with partial
+/// pivoting: each column is eliminated using its largest remaining entry.
+///
+/// Both factors share one array, `U` on and above the diagonal and `L` below
+/// it, with the ones on `L`'s diagonal left implicit.
+struct LuDecomposition<const D: usize> {
+    lu: [[f64; D]; D],
+    /// The row of the original matrix that ended up in each row.
+    rows: [usize; D],
+    /// -1 after an odd number of row swaps, 1 otherwise.
+    sign: f64,
+}
 
-    fn submatrix(&self, row: usize, col: usize) -> Matrix<3> {
-        let mut submatrix = Matrix::new();
-        let mut i = 0;
-        let mut j = 0;
+impl<const D: usize> LuDecomposition<D> {
+    fn new(matrix: &Matrix<D>) -> Self {
+        let mut lu = matrix.entries;
+        let mut rows = std::array::from_fn(|row| row);
+        let mut sign = 1.0;
+
+        for col in 0..D {
+            let pivot = (col..D)
+                .max_by(|&a, &b| lu[a][col].abs().total_cmp(&lu[b][col].abs()))
+                .unwrap_or(col);
+            if pivot != col {
+                lu.swap(pivot, col);
+                rows.swap(pivot, col);
+                sign = -sign;
+            }
 
-        for (index, element) in self.entries.iter().enumerate() {
-            if index == row {
+            let diagonal = lu[col][col];
+            if diagonal == 0.0 {
+                // Nothing left to eliminate with, the determinant is zero.
                 continue;
             }
-            for (index, entry) in element.iter().enumerate() {
-                if index == col {
-                    continue;
+            let pivot_row = lu[col];
+            for row in lu.iter_mut().skip(col + 1) {
+                let factor = row[col] / diagonal;
+                row[col] = factor;
+                for (entry, pivot_entry) in row.iter_mut().zip(pivot_row).skip(col + 1) {
+                    *entry -= factor * pivot_entry;
                 }
-
-                submatrix[i][j] = *entry;
-
-                j = match j {
-                    2 => 0,
-                    _ => j + 1,
-                };
             }
-
-            i = match i {
-                2 => 0,
-                _ => i + 1,
-            };
         }
-        submatrix
-    }
 
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
+        Self { lu, rows, sign }
     }
 
-    fn inverse(&self) -> Matrix<4> {
-        assert!(self.is_invertible());
-
-        let mut matrix = Matrix::new();
-        let determinant = self.determinant();
+    fn determinant(&self) -> f64 {
+        self.sign * (0..D).map(|i| self.lu[i][i]).product::<f64>()
+    }
 
-        for row in 0..4 {
-            for col in 0..4 {
-                let cofactor = self.cofactor(row, col);
-                matrix[col][row] = cofactor / determinant;
-            }
+    /// Solves by forward substitution through `L`, then back substitution
+    /// through `U`. Only meaningful when the matrix isn't singular.
+    fn solve(&self, b: [f64; D]) -> [f64; D] {
+        let mut x: [f64; D] = std::array::from_fn(|row| b[self.rows[row]]);
+        for row in 0..D {
+            let sum: f64 = (0..row).map(|k| self.lu[row][k] * x[k]).sum();
+            x[row] -= sum;
         }
-        matrix
+        for row in (0..D).rev() {
+            let sum: f64 = (row + 1..D).map(|k| self.lu[row][k] * x[k]).sum();
+            x[row] = (x[row] - sum) / self.lu[row][row];
+        }
+        x
     }
+}
 
-    fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
+impl Matrix<4> {
+    pub fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, 0.0, 0.0, x],
             [0.0, 1.0, 0.0, y],
@@ -179,7 +186,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn scaling(x: f64, y: f64, z: f64) -> Matrix<4> {
+    pub fn scaling(x: f64, y: f64, z: f64) -> Matrix<4> {
         Matrix::from([
             [x, 0.0, 0.0, 0.0],
             [0.0, y, 0.0, 0.0],
@@ -188,7 +195,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_x(r: f64) -> Matrix<4> {
+    pub fn rotation_x(r: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, 0.0, 0.0, 0.0],
             [0.0, r.cos(), -r.sin(), 0.0],
@@ -197,7 +204,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_y(r: f64) -> Matrix<4> {
+    pub fn rotation_y(r: f64) -> Matrix<4> {
         Matrix::from([
             [r.cos(), 0.0, r.sin(), 0.0],
             [0.0, 1.0, 0.0, 0.0],
@@ -206,7 +213,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_z(r: f64) -> Matrix<4> {
+    pub fn rotation_z(r: f64) -> Matrix<4> {
         Matrix::from([
             [r.cos(), -r.sin(), 0.0, 0.0],
             [r.sin(), r.cos(), 0.0, 0.0],
@@ -215,7 +222,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn shearing(x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Matrix<4> {
+    pub fn shearing(x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, x_y, x_z, 0.0],
             [y_x, 1.0, y_z, 0.0],
@@ -223,13 +230,29 @@ impl Matrix<4> {
             [0.0, 0.0, 0.0, 1.0],
         ])
     }
+
+    /// Orients the world relative to an eye at `from` looking toward `to`.
+    pub fn view_transform(from: Point, to: Point, up: Vector) -> Matrix<4> {
+        let forward = (to - from).normalize();
+        let left = forward.cross(&up.normalize());
+        let true_up = left.cross(&forward);
+
+        let orientation = Matrix::from([
+            [left.x, left.y, left.z, 0.0],
+            [true_up.x, true_up.y, true_up.z, 0.0],
+            [-forward.x, -forward.y, -forward.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        orientation * Matrix::translation(-from.x, -from.y, -from.z)
+    }
 }
 
 impl<const D: usize> fmt::Debug for Matrix<D> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let width = 10;
         let precision = 5;
-        writeln!(f, "");
+        writeln!(f)?;
 
         for row in 0..D {
             write!(f, "| {0:>width$}", format!("{0:.precision$}", self[row][0]))?;
@@ -258,17 +281,13 @@ impl<const D: usize> PartialEq<Self> for Matrix<D> {
     fn eq(&self, other: &Matrix<D>) -> bool {
         for row in 0..D {
             for col in 0..D {
-                if equal(self[row][col], other[row][col]) == false {
+                if !equal(self[row][col], other[row][col]) {
                     return false;
                 }
             }
         }
         true
     }
-
-    fn ne(&self, other: &Matrix<D>) -> bool {
-        !self.eq(other)
-    }
 }
 
 impl<const D: usize> ops::Mul<Matrix<D>> for Matrix<D> {
@@ -461,6 +480,18 @@ mod tests {
         assert_eq!(expected, a.transpose());
     }
 
+    #[test]
+    fn should_construct_the_identity_matrix() {
+        let expected = Matrix::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_eq!(expected, Matrix::identity());
+    }
+
     #[test]
     fn should_transpose_identity_matrix() {
         let identity_matrix = Matrix::from([
@@ -482,40 +513,6 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
-    #[test]
-    fn should_return_a_2_x_2_submatrix() {
-        let a = Matrix::from([[1.0, 5.0, 0.0], [-3.0, 2.0, 7.0], [0.0, 6.0, -3.0]]);
-        let expected = Matrix::from([[-3.0, 2.0], [0.0, 6.0]]);
-        assert_eq!(expected, a.submatrix(0, 2));
-    }
-
-    #[test]
-    fn should_return_a_3_x_3_submatrix() {
-        let a = Matrix::from([
-            [-6.0, 1.0, 1.0, 6.0],
-            [-8.0, 5.0, 8.0, 6.0],
-            [-1.0, 0.0, 8.0, 2.0],
-            [-7.0, 1.0, -1.0, 1.0],
-        ]);
-
-        let expected = Matrix::from([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]]);
-        let actual = a.submatrix(2, 1);
-        assert_eq!(expected, actual);
-    }
-
-    #[test]
-    fn should_return_minor_of_3_by_3_matrix() {
-        let a = Matrix::from([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
-        assert_eq!(a.minor(1, 0), 25.0);
-    }
-
-    #[test]
-    fn should_return_cofactor_of_3_by_3_matrix() {
-        let a = Matrix::from([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
-        assert_eq!(a.cofactor(0, 0), -12.0);
-        assert_eq!(a.cofactor(1, 0), -25.0);
-    }
-
     #[test]
     fn should_calculate_the_determinant_of_a_3_by_3_matrix() {
         let a = Matrix::from([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
@@ -530,7 +527,7 @@ mod tests {
             [1.0, 2.0, -9.0, 6.0],
             [-6.0, 7.0, 7.0, -9.0],
         ]);
-        assert_eq!(a.determinant(), -4071.0);
+        assert!(equal(a.determinant(), -4071.0));
     }
 
     #[test]
@@ -552,6 +549,44 @@ mod tests {
         assert!(!b.is_invertible());
     }
 
+    #[test]
+    fn should_not_invert_a_singular_matrix() {
+        let flattened = Matrix::scaling(1.0, 0.0, 1.0);
+        let degenerate_shear = Matrix::shearing(1.0, 0.0, 1.0, 0.0, 0.0, 0.0);
+
+        assert!(flattened.try_inverse().is_none());
+        assert!(degenerate_shear.try_inverse().is_none());
+    }
+
+    #[test]
+    fn should_not_invert_a_nearly_singular_matrix() {
+        let a = Matrix::from([
+            [1.0, 2.0, 3.0, 0.0],
+            [4.0, 5.0, 6.0, 0.0],
+            [7.0, 8.0, 9.0 + 1e-14, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_ne!(a.determinant(), 0.0);
+        assert!(a.try_inverse().is_none());
+    }
+
+    #[test]
+    fn should_invert_a_tiny_but_regular_matrix() {
+        let a = Matrix::scaling(0.001, 0.001, 0.001);
+
+        assert_eq!(
+            a.try_inverse(),
+            Some(Matrix::scaling(1000.0, 1000.0, 1000.0))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix is not invertible")]
+    fn should_panic_when_inverting_a_singular_matrix() {
+        Matrix::scaling(0.0, 1.0, 1.0).inverse();
+    }
+
     #[test]
     fn should_calculate_the_inverse_of_a_matrix() {
         let a = Matrix::from([
@@ -570,14 +605,59 @@ mod tests {
 
         let b = a.inverse();
 
-        assert_eq!(a.determinant(), 532.0);
-        assert_eq!(a.cofactor(2, 3), -160.0);
-        assert_eq!(a.cofactor(3, 2), 105.0);
-        assert_eq!(-160.0 / 532.0, b[3][2]);
-        assert_eq!(105.0 / 532.0, b[2][3]);
+        assert!(equal(a.determinant(), 532.0));
+        assert!(equal(b[3][2], -160.0 / 532.0));
+        assert!(equal(b[2][3], 105.0 / 532.0));
         assert_eq!(expected, b);
     }
 
+    #[test]
+    fn should_calculate_the_inverse_of_a_3_by_3_matrix() {
+        let a = Matrix::from([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+        let b = a.inverse();
+
+        assert_eq!(a * b, Matrix::identity());
+        assert!(equal(b[0][0], 56.0 / -196.0));
+        assert!(equal(b[0][1], 28.0 / -196.0));
+    }
+
+    #[test]
+    fn should_pivot_around_a_zero_on_the_diagonal() {
+        let a = Matrix::from([[0.0, 1.0], [1.0, 0.0]]);
+
+        assert_eq!(a.determinant(), -1.0);
+        assert_eq!(a.inverse(), a);
+    }
+
+    #[test]
+    fn should_invert_a_larger_matrix() {
+        let a = Matrix::from([
+            [4.0, -2.0, 1.0, 3.0, 0.0],
+            [3.0, 6.0, -4.0, 2.0, 1.0],
+            [2.0, 1.0, 8.0, -5.0, 2.0],
+            [1.0, 0.0, 2.0, 7.0, -3.0],
+            [0.0, 5.0, -1.0, 1.0, 9.0],
+        ]);
+
+        assert_eq!(a * a.inverse(), Matrix::identity());
+        assert_eq!(a.inverse() * a, Matrix::identity());
+    }
+
+    #[test]
+    fn should_solve_a_linear_system() {
+        let a = Matrix::from([[2.0, 1.0, -1.0], [-3.0, -1.0, 2.0], [-2.0, 1.0, 2.0]]);
+
+        let x = a.solve([8.0, -11.0, -3.0]).unwrap();
+
+        assert!(equal(x[0], 2.0));
+        assert!(equal(x[1], 3.0));
+        assert!(equal(x[2], -1.0));
+        assert!(Matrix::from([[1.0, 2.0], [2.0, 4.0]])
+            .solve([1.0, 2.0])
+            .is_none());
+    }
+
     #[test]
     fn should_calculate_the_inverse_of_another_matrix() {
         let a = Matrix::from([
@@ -803,4 +883,53 @@ mod tests {
         let t = c * b * a;
         assert_eq!(t * p, Point::new(15.0, 0.0, 7.0));
     }
+
+    #[test]
+    fn should_use_identity_for_the_default_orientation() {
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(0.0, 0.0, -1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(Matrix::view_transform(from, to, up), Matrix::identity());
+    }
+
+    #[test]
+    fn should_look_in_the_positive_z_direction() {
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(0.0, 0.0, 1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            Matrix::view_transform(from, to, up),
+            Matrix::scaling(-1.0, 1.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn should_move_the_world_with_the_view_transformation() {
+        let from = Point::new(0.0, 0.0, 8.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            Matrix::view_transform(from, to, up),
+            Matrix::translation(0.0, 0.0, -8.0)
+        );
+    }
+
+    #[test]
+    fn should_create_an_arbitrary_view_transformation() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        let expected = Matrix::from([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.00000],
+            [0.00000, 0.00000, 0.00000, 1.00000],
+        ]);
+
+        assert_eq!(Matrix::view_transform(from, to, up), expected);
+    }
 }
@@ -0,0 +1,509 @@
+use crate::group::Group;
+use crate::point::Point;
+use crate::shape::Shape;
+use crate::triangle::{SmoothTriangle, Triangle};
+use crate::vector::Vector;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "could not read OBJ file: {}", error),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(error: io::Error) -> Self {
+        ObjError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MeshTriangle {
+    Flat(Triangle),
+    Smooth(SmoothTriangle),
+}
+
+impl MeshTriangle {
+    pub fn into_shape(self) -> Box<dyn Shape> {
+        match self {
+            MeshTriangle::Flat(triangle) => Box::new(triangle),
+            MeshTriangle::Smooth(triangle) => Box::new(triangle),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjGroup {
+    pub name: String,
+    pub triangles: Vec<MeshTriangle>,
+}
+
+impl ObjGroup {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            triangles: vec![],
+        }
+    }
+
+    pub fn into_group(self) -> Group {
+        let mut group = Group::new();
+        for triangle in self.triangles {
+            group.add_boxed_child(triangle.into_shape());
+        }
+        group
+    }
+}
+
+/// The geometry read from a Wavefront OBJ file.
+///
+/// Only `v`, `vn`, `f` and `g` statements are understood; the line numbers
+/// of everything else end up in `ignored_lines`.
+#[derive(Debug, Clone)]
+pub struct ObjFile {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vector>,
+    pub default_group: ObjGroup,
+    pub groups: Vec<ObjGroup>,
+    pub ignored_lines: Vec<usize>,
+}
+
+impl ObjFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        Self::parse(BufReader::new(File::open(path)?))
+    }
+
+    pub fn parse(reader: impl BufRead) -> Result<Self, ObjError> {
+        let mut obj = ObjFile {
+            vertices: vec![],
+            normals: vec![],
+            default_group: ObjGroup::new("default"),
+            groups: vec![],
+            ignored_lines: vec![],
+        };
+        let mut current_group: Option<usize> = None;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line?;
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                Some("v") => {
+                    let [x, y, z] = parse_coordinates(tokens, line_number)?;
+                    obj.vertices.push(Point::new(x, y, z));
+                }
+                Some("vn") => {
+                    let [x, y, z] = parse_coordinates(tokens, line_number)?;
+                    obj.normals.push(Vector::new(x, y, z));
+                }
+                Some("f") => {
+                    let triangles = obj.parse_face(tokens, line_number)?;
+                    let group = match current_group {
+                        Some(index) => &mut obj.groups[index],
+                        None => &mut obj.default_group,
+                    };
+                    group.triangles.extend(triangles);
+                }
+                Some("g") => {
+                    let name = tokens.collect::<Vec<_>>().join(" ");
+                    if name.is_empty() {
+                        return Err(parse_error(line_number, "group statement without a name"));
+                    }
+                    let index = match obj.groups.iter().position(|g| g.name == name) {
+                        Some(index) => index,
+                        None => {
+                            obj.groups.push(ObjGroup::new(&name));
+                            obj.groups.len() - 1
+                        }
+                    };
+                    current_group = Some(index);
+                }
+                None => {}
+                Some(_) => obj.ignored_lines.push(line_number),
+            }
+        }
+
+        Ok(obj)
+    }
+
+    pub fn group(&self, name: &str) -> Option<&ObjGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// Every triangle in the file, with each named group as a subgroup.
+    pub fn into_group(self) -> Group {
+        let mut group = self.default_group.into_group();
+        for named_group in self.groups {
+            group.add_child(named_group.into_group());
+        }
+        group
+    }
+
+    /// Triangulates a polygon face as a fan around its first vertex.
+    fn parse_face<'a>(
+        &self,
+        tokens: impl Iterator<Item = &'a str>,
+        line: usize,
+    ) -> Result<Vec<MeshTriangle>, ObjError> {
+        let mut vertices = vec![];
+        for token in tokens {
+            vertices.push(self.parse_face_vertex(token, line)?);
+        }
+
+        if vertices.len() < 3 {
+            return Err(parse_error(line, "face needs at least three vertices"));
+        }
+
+        let triangles = (1..vertices.len() - 1)
+            .map(|i| {
+                let (p1, n1) = vertices[0];
+                let (p2, n2) = vertices[i];
+                let (p3, n3) = vertices[i + 1];
+                match (n1, n2, n3) {
+                    (Some(n1), Some(n2), Some(n3)) => {
+                        MeshTriangle::Smooth(SmoothTriangle::new(p1, p2, p3, n1, n2, n3))
+                    }
+                    _ => MeshTriangle::Flat(Triangle::new(p1, p2, p3)),
+                }
+            })
+            .collect();
+
+        Ok(triangles)
+    }
+
+    /// Resolves one `v`, `v/vt`, `v//vn` or `v/vt/vn` reference of a face.
+    fn parse_face_vertex(
+        &self,
+        token: &str,
+        line: usize,
+    ) -> Result<(Point, Option<Vector>), ObjError> {
+        let mut parts = token.split('/');
+
+        let vertex_index = parts.next().unwrap_or_default();
+        let vertex = *resolve_index(&self.vertices, vertex_index, line, "vertex")?;
+
+        // Texture coordinates are not used yet.
+        let _texture = parts.next();
+
+        let normal = match parts.next() {
+            Some(index) if !index.is_empty() => {
+                Some(*resolve_index(&self.normals, index, line, "normal")?)
+            }
+            _ => None,
+        };
+
+        Ok((vertex, normal))
+    }
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> ObjError {
+    ObjError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+fn parse_coordinates<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<[f64; 3], ObjError> {
+    let values = tokens
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|_| parse_error(line, format!("invalid number '{}'", token)))
+        })
+        .collect::<Result<Vec<f64>, ObjError>>()?;
+
+    // A fourth (w) component is allowed but ignored.
+    match values[..] {
+        [x, y, z] | [x, y, z, _] => Ok([x, y, z]),
+        _ => Err(parse_error(
+            line,
+            format!("expected 3 coordinates, found {}", values.len()),
+        )),
+    }
+}
+
+/// Looks up a 1-based (or negative, relative to the end) OBJ index.
+fn resolve_index<'a, T>(
+    items: &'a [T],
+    index: &str,
+    line: usize,
+    kind: &str,
+) -> Result<&'a T, ObjError> {
+    let value = index
+        .parse::<i64>()
+        .map_err(|_| parse_error(line, format!("invalid {} index '{}'", kind, index)))?;
+
+    let position = match value {
+        v if v > 0 => Some(v as usize - 1),
+        v if v < 0 => items.len().checked_sub(v.unsigned_abs() as usize),
+        _ => None,
+    };
+
+    position
+        .and_then(|position| items.get(position))
+        .ok_or_else(|| parse_error(line, format!("{} index {} out of range", kind, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+    use crate::ray::Ray;
+
+    fn parse(input: &str) -> Result<ObjFile, ObjError> {
+        ObjFile::parse(input.as_bytes())
+    }
+
+    fn flat(triangle: &MeshTriangle) -> &Triangle {
+        match triangle {
+            MeshTriangle::Flat(triangle) => triangle,
+            MeshTriangle::Smooth(_) => panic!("expected a flat triangle"),
+        }
+    }
+
+    fn smooth(triangle: &MeshTriangle) -> &SmoothTriangle {
+        match triangle {
+            MeshTriangle::Smooth(triangle) => triangle,
+            MeshTriangle::Flat(_) => panic!("expected a smooth triangle"),
+        }
+    }
+
+    #[test]
+    fn should_ignore_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night.";
+
+        let obj = parse(gibberish).unwrap();
+
+        assert_eq!(obj.ignored_lines, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn should_parse_vertex_records() {
+        let input = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+
+        let obj = parse(input).unwrap();
+
+        assert_eq!(obj.vertices[0], Point::new(-1.0, 1.0, 0.0));
+        assert_eq!(obj.vertices[1], Point::new(-1.0, 0.5, 0.0));
+        assert_eq!(obj.vertices[2], Point::new(1.0, 0.0, 0.0));
+        assert_eq!(obj.vertices[3], Point::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn should_parse_triangle_faces() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4";
+
+        let obj = parse(input).unwrap();
+        let t1 = flat(&obj.default_group.triangles[0]);
+        let t2 = flat(&obj.default_group.triangles[1]);
+
+        assert_eq!(t1.p1, obj.vertices[0]);
+        assert_eq!(t1.p2, obj.vertices[1]);
+        assert_eq!(t1.p3, obj.vertices[2]);
+        assert_eq!(t2.p1, obj.vertices[0]);
+        assert_eq!(t2.p2, obj.vertices[2]);
+        assert_eq!(t2.p3, obj.vertices[3]);
+    }
+
+    #[test]
+    fn should_triangulate_polygons() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+
+        let obj = parse(input).unwrap();
+        let triangles = &obj.default_group.triangles;
+
+        assert_eq!(triangles.len(), 3);
+        for (i, triangle) in triangles.iter().enumerate() {
+            let t = flat(triangle);
+            assert_eq!(t.p1, obj.vertices[0]);
+            assert_eq!(t.p2, obj.vertices[i + 1]);
+            assert_eq!(t.p3, obj.vertices[i + 2]);
+        }
+    }
+
+    #[test]
+    fn should_put_triangles_in_named_groups() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+
+        let obj = parse(input).unwrap();
+        let t1 = flat(&obj.group("FirstGroup").unwrap().triangles[0]);
+        let t2 = flat(&obj.group("SecondGroup").unwrap().triangles[0]);
+
+        assert!(obj.default_group.triangles.is_empty());
+        assert_eq!(t1.p1, obj.vertices[0]);
+        assert_eq!(t1.p2, obj.vertices[1]);
+        assert_eq!(t1.p3, obj.vertices[2]);
+        assert_eq!(t2.p1, obj.vertices[0]);
+        assert_eq!(t2.p2, obj.vertices[2]);
+        assert_eq!(t2.p3, obj.vertices[3]);
+    }
+
+    #[test]
+    fn should_parse_vertex_normal_records() {
+        let input = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3";
+
+        let obj = parse(input).unwrap();
+
+        assert_eq!(obj.normals[0], Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(obj.normals[1], Vector::new(0.707, 0.0, -0.707));
+        assert_eq!(obj.normals[2], Vector::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn should_parse_faces_with_normals() {
+        let input = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/0/3 2/102/1 3/14/2";
+
+        let obj = parse(input).unwrap();
+
+        for triangle in &obj.default_group.triangles {
+            let t = smooth(triangle);
+            assert_eq!(t.p1, obj.vertices[0]);
+            assert_eq!(t.p2, obj.vertices[1]);
+            assert_eq!(t.p3, obj.vertices[2]);
+            assert_eq!(t.n1, obj.normals[2]);
+            assert_eq!(t.n2, obj.normals[0]);
+            assert_eq!(t.n3, obj.normals[1]);
+        }
+    }
+
+    #[test]
+    fn should_resolve_negative_indices_from_the_end() {
+        let input = "v 0 1 0
+v -1 0 0
+v 1 0 0
+f -3 -2 -1";
+
+        let obj = parse(input).unwrap();
+        let t = flat(&obj.default_group.triangles[0]);
+
+        assert_eq!(t.p1, obj.vertices[0]);
+        assert_eq!(t.p3, obj.vertices[2]);
+    }
+
+    #[test]
+    fn should_report_the_line_of_an_out_of_range_index() {
+        let input = "v 0 1 0
+v -1 0 0
+f 1 2 3";
+
+        match parse(input) {
+            Err(ObjError::Parse { line, message }) => {
+                assert_eq!(line, 3);
+                assert_eq!(message, "vertex index 3 out of range");
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_report_the_line_of_an_invalid_number() {
+        let input = "v 0 1 0\nv -1 zero 0";
+
+        let error = parse(input).unwrap_err();
+
+        assert_eq!(error.to_string(), "line 2: invalid number 'zero'");
+    }
+
+    #[test]
+    fn should_reject_degenerate_faces() {
+        let input = "v 0 1 0\nv -1 0 0\nf 1 2";
+
+        let error = parse(input).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "line 3: face needs at least three vertices"
+        );
+    }
+
+    #[test]
+    fn should_convert_an_obj_file_to_a_group() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+
+        let group = parse(input).unwrap().into_group();
+
+        assert_eq!(group.children().len(), 3);
+    }
+
+    #[test]
+    fn should_place_the_group_with_a_transform() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+g Triangle
+f 1 2 3";
+        let mut group = parse(input).unwrap().into_group();
+        group.set_transform(Matrix::translation(0.0, 0.0, 5.0));
+        let ray = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = group.intersect(&ray);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 10.0);
+    }
+}
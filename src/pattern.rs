@@ -0,0 +1,652 @@
+use crate::color::Color;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::shape::Shape;
+use crate::transform::{InvertibleTransform, SingularTransformError};
+use std::fmt;
+
+/// A color that varies over space, evaluated in its own pattern space.
+///
+/// Like shapes, implementors only deal with pattern space in
+/// `local_pattern_at`; going from world to object to pattern space is done
+/// once here. Patterns built from other patterns pass their pattern space
+/// point to `pattern_at` of the inner pattern, which applies its own transform.
+pub trait Pattern: fmt::Debug + Send + Sync {
+    fn invertible_transform(&self) -> &InvertibleTransform;
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform);
+
+    fn transform(&self) -> &Matrix<4> {
+        self.invertible_transform().matrix()
+    }
+
+    /// Sets the transform, inverting it once. Panics if it isn't invertible.
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.set_invertible_transform(InvertibleTransform::new(transform));
+    }
+
+    /// Like `set_transform`, but leaves the pattern as it was when
+    /// `transform` isn't invertible.
+    fn try_set_transform(&mut self, transform: Matrix<4>) -> Result<(), SingularTransformError> {
+        self.set_invertible_transform(InvertibleTransform::try_new(transform)?);
+        Ok(())
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color;
+
+    /// The color at `point`, given in the space this pattern is embedded in.
+    fn pattern_at(&self, point: Point) -> Color {
+        self.local_pattern_at(*self.invertible_transform().inverse() * point)
+    }
+
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Point) -> Color {
+        self.pattern_at(object.world_to_object(world_point))
+    }
+}
+
+/// The same color everywhere, mostly useful as a component of other patterns.
+#[derive(Debug, Clone)]
+pub struct SolidPattern {
+    transform: InvertibleTransform,
+    pub color: Color,
+}
+
+impl SolidPattern {
+    pub fn new(color: Color) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            color,
+        }
+    }
+}
+
+impl Pattern for SolidPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, _point: Point) -> Color {
+        self.color
+    }
+
+    fn pattern_at(&self, _point: Point) -> Color {
+        self.color
+    }
+}
+
+fn solid(color: Color) -> Box<dyn Pattern> {
+    Box::new(SolidPattern::new(color))
+}
+
+/// Alternates between `a` and `b` every unit along x.
+#[derive(Debug)]
+pub struct StripePattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl StripePattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for StripePattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        if point.x.floor() as i64 % 2 == 0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// Blends linearly from `a` at x = 0 to `b` at x = 1.
+#[derive(Debug)]
+pub struct GradientPattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl GradientPattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for GradientPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let a = self.a.pattern_at(point);
+        let b = self.b.pattern_at(point);
+        a + (b - a) * (point.x - point.x.floor())
+    }
+}
+
+/// Concentric rings around the y axis, alternating between `a` and `b`.
+#[derive(Debug)]
+pub struct RingPattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl RingPattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for RingPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        if distance.floor() as i64 % 2 == 0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// Rings that blend from `a` to `b` across each unit of distance from the y axis.
+#[derive(Debug)]
+pub struct RadialGradientPattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl RadialGradientPattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for RadialGradientPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let distance = (point.x.powi(2) + point.z.powi(2)).sqrt();
+        let a = self.a.pattern_at(point);
+        let b = self.b.pattern_at(point);
+        a + (b - a) * (distance - distance.floor())
+    }
+}
+
+/// Alternating unit cubes of `a` and `b` in all three dimensions.
+#[derive(Debug)]
+pub struct CheckersPattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl CheckersPattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self::nested(solid(a), solid(b))
+    }
+
+    pub fn nested(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for CheckersPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let sum = point.x.floor() + point.y.floor() + point.z.floor();
+        if sum as i64 % 2 == 0 {
+            self.a.pattern_at(point)
+        } else {
+            self.b.pattern_at(point)
+        }
+    }
+}
+
+/// The average of two patterns.
+#[derive(Debug)]
+pub struct BlendedPattern {
+    transform: InvertibleTransform,
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+}
+
+impl BlendedPattern {
+    pub fn new(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            a,
+            b,
+        }
+    }
+}
+
+impl Pattern for BlendedPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        (self.a.pattern_at(point) + self.b.pattern_at(point)) * 0.5
+    }
+}
+
+/// Jitters the point handed to `pattern` with Perlin noise, for organic looking surfaces.
+#[derive(Debug)]
+pub struct PerturbedPattern {
+    transform: InvertibleTransform,
+    pub pattern: Box<dyn Pattern>,
+    /// How far, at most, a point is moved along each axis.
+    pub scale: f64,
+}
+
+impl PerturbedPattern {
+    pub fn new(pattern: Box<dyn Pattern>, scale: f64) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            pattern,
+            scale,
+        }
+    }
+}
+
+impl Pattern for PerturbedPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        // Sample the noise at offset positions so the three axes move independently.
+        let dx = perlin_noise(point.x, point.y, point.z);
+        let dy = perlin_noise(point.x + 31.4, point.y + 27.1, point.z + 18.3);
+        let dz = perlin_noise(point.x - 12.9, point.y - 41.7, point.z + 53.2);
+
+        let jittered = Point::new(
+            point.x + dx * self.scale,
+            point.y + dy * self.scale,
+            point.z + dz * self.scale,
+        );
+        self.pattern.pattern_at(jittered)
+    }
+}
+
+/// Ken Perlin's improved gradient noise, roughly within -1..1 and 0 at integer lattice points.
+pub fn perlin_noise(x: f64, y: f64, z: f64) -> f64 {
+    let (xi, yi, zi) = (x.floor() as i64, y.floor() as i64, z.floor() as i64);
+    let (xf, yf, zf) = (x - x.floor(), y - y.floor(), z - z.floor());
+    let (u, v, w) = (fade(xf), fade(yf), fade(zf));
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        let hash = lattice_hash(xi + dx, yi + dy, zi + dz);
+        gradient(hash, xf - dx as f64, yf - dy as f64, zf - dz as f64)
+    };
+
+    let x1 = lerp(u, corner(0, 0, 0), corner(1, 0, 0));
+    let x2 = lerp(u, corner(0, 1, 0), corner(1, 1, 0));
+    let x3 = lerp(u, corner(0, 0, 1), corner(1, 0, 1));
+    let x4 = lerp(u, corner(0, 1, 1), corner(1, 1, 1));
+
+    lerp(w, lerp(v, x1, x2), lerp(v, x3, x4))
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// A deterministic stand-in for Perlin's permutation table.
+fn lattice_hash(x: i64, y: i64, z: i64) -> u64 {
+    let mut h = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h
+}
+
+/// Dot product with one of the twelve edge directions of a cube.
+fn gradient(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sphere::Sphere;
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn black() -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    /// A pattern that returns the point it was evaluated at as a color.
+    #[derive(Debug)]
+    pub struct TestPattern {
+        transform: InvertibleTransform,
+    }
+
+    impl TestPattern {
+        pub fn new() -> Self {
+            Self {
+                transform: InvertibleTransform::identity(),
+            }
+        }
+    }
+
+    impl Default for TestPattern {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Pattern for TestPattern {
+        fn invertible_transform(&self) -> &InvertibleTransform {
+            &self.transform
+        }
+
+        fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+            self.transform = transform;
+        }
+
+        fn local_pattern_at(&self, point: Point) -> Color {
+            Color::new(point.x, point.y, point.z)
+        }
+    }
+
+    #[test]
+    fn should_have_identity_as_default_pattern_transformation() {
+        let pattern = TestPattern::new();
+        assert_eq!(*pattern.transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_assign_a_pattern_transformation() {
+        let mut pattern = TestPattern::new();
+        pattern.set_transform(Matrix::translation(1.0, 2.0, 3.0));
+        assert_eq!(*pattern.transform(), Matrix::translation(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn should_apply_an_object_transformation() {
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        let pattern = TestPattern::new();
+
+        let c = pattern.pattern_at_shape(&shape, Point::new(2.0, 3.0, 4.0));
+
+        assert_eq!(c, Color::new(1.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn should_apply_a_pattern_transformation() {
+        let shape = Sphere::new();
+        let mut pattern = TestPattern::new();
+        pattern.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+
+        let c = pattern.pattern_at_shape(&shape, Point::new(2.0, 3.0, 4.0));
+
+        assert_eq!(c, Color::new(1.0, 1.5, 2.0));
+    }
+
+    #[test]
+    fn should_apply_both_an_object_and_a_pattern_transformation() {
+        let mut shape = Sphere::new();
+        shape.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+        let mut pattern = TestPattern::new();
+        pattern.set_transform(Matrix::translation(0.5, 1.0, 1.5));
+
+        let c = pattern.pattern_at_shape(&shape, Point::new(2.5, 3.0, 3.5));
+
+        assert_eq!(c, Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn should_keep_stripes_constant_in_y_and_z() {
+        let pattern = StripePattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 1.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 2.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 1.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 2.0)), white());
+    }
+
+    #[test]
+    fn should_alternate_stripes_in_x() {
+        let pattern = StripePattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.9, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(1.0, 0.0, 0.0)), black());
+        assert_eq!(pattern.pattern_at(Point::new(-0.1, 0.0, 0.0)), black());
+        assert_eq!(pattern.pattern_at(Point::new(-1.0, 0.0, 0.0)), black());
+        assert_eq!(pattern.pattern_at(Point::new(-1.1, 0.0, 0.0)), white());
+    }
+
+    #[test]
+    fn should_linearly_interpolate_a_gradient() {
+        let pattern = GradientPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.25, 0.0, 0.0)),
+            Color::new(0.75, 0.75, 0.75)
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.5, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.75, 0.0, 0.0)),
+            Color::new(0.25, 0.25, 0.25)
+        );
+    }
+
+    #[test]
+    fn should_extend_rings_in_x_and_z() {
+        let pattern = RingPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(1.0, 0.0, 0.0)), black());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 1.0)), black());
+        assert_eq!(pattern.pattern_at(Point::new(0.708, 0.0, 0.708)), black());
+    }
+
+    #[test]
+    fn should_blend_a_radial_gradient_with_distance() {
+        let pattern = RadialGradientPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.0, 0.0, 0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(pattern.pattern_at(Point::new(0.6, 0.0, 0.8)), white());
+    }
+
+    #[test]
+    fn should_repeat_checkers_in_x() {
+        let pattern = CheckersPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.99, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(1.01, 0.0, 0.0)), black());
+    }
+
+    #[test]
+    fn should_repeat_checkers_in_y() {
+        let pattern = CheckersPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.99, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 1.01, 0.0)), black());
+    }
+
+    #[test]
+    fn should_repeat_checkers_in_z() {
+        let pattern = CheckersPattern::new(white(), black());
+
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.0)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 0.99)), white());
+        assert_eq!(pattern.pattern_at(Point::new(0.0, 0.0, 1.01)), black());
+    }
+
+    #[test]
+    fn should_evaluate_nested_patterns_in_their_own_space() {
+        let mut inner = TestPattern::new();
+        inner.set_transform(Matrix::translation(1.0, 0.0, 0.0));
+        let mut pattern = StripePattern::nested(Box::new(inner), solid(black()));
+        pattern.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+
+        let c = pattern.pattern_at(Point::new(1.0, 2.0, 3.0));
+
+        assert_eq!(c, Color::new(-0.5, 1.0, 1.5));
+    }
+
+    #[test]
+    fn should_average_blended_patterns() {
+        let stripes = StripePattern::new(white(), black());
+        let mut rotated = StripePattern::new(white(), black());
+        rotated.set_transform(Matrix::rotation_y(std::f64::consts::PI / 2.0));
+        let pattern = BlendedPattern::new(Box::new(stripes), Box::new(rotated));
+
+        assert_eq!(pattern.pattern_at(Point::new(0.5, 0.0, -0.5)), white());
+        assert_eq!(
+            pattern.pattern_at(Point::new(1.5, 0.0, -0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+
+    #[test]
+    fn should_produce_smooth_bounded_noise() {
+        assert_eq!(perlin_noise(1.0, 2.0, 3.0), 0.0);
+        assert_eq!(perlin_noise(0.3, 0.7, 0.1), perlin_noise(0.3, 0.7, 0.1));
+
+        for i in 0..1000 {
+            let t = i as f64 * 0.137;
+            let n = perlin_noise(t, t * 0.5, -t);
+            assert!((-1.5..=1.5).contains(&n));
+            let nearby = perlin_noise(t + 0.001, t * 0.5, -t);
+            assert!((n - nearby).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn should_jitter_the_point_of_a_perturbed_pattern() {
+        let pattern = PerturbedPattern::new(Box::new(TestPattern::new()), 0.5);
+        let point = Point::new(0.3, 0.6, 0.9);
+
+        let c = pattern.pattern_at(point);
+
+        assert_ne!(c, Color::new(point.x, point.y, point.z));
+        assert!((c.r - point.x).abs() <= 0.75);
+        assert!((c.g - point.y).abs() <= 0.75);
+        assert!((c.b - point.z).abs() <= 0.75);
+    }
+}
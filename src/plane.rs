@@ -0,0 +1,130 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+use crate::EPSILON;
+
+/// An infinite plane spanning x and z in object space.
+#[derive(Debug, Clone, Default)]
+pub struct Plane {
+    properties: ShapeProperties,
+}
+
+impl Plane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Shape for Plane {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        if ray.direction.y.abs() < EPSILON {
+            return Intersections::default();
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        Intersections::new(vec![Intersection::new(t, self)])
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: &Intersection) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_have_a_constant_normal_everywhere() {
+        let plane = Plane::new();
+
+        assert_eq!(
+            plane.local_normal_at(Point::new(0.0, 0.0, 0.0), &Intersection::new(0.0, &plane)),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            plane.local_normal_at(
+                Point::new(10.0, 0.0, -10.0),
+                &Intersection::new(0.0, &plane)
+            ),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            plane.local_normal_at(
+                Point::new(-5.0, 0.0, 150.0),
+                &Intersection::new(0.0, &plane)
+            ),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_not_intersect_a_parallel_ray() {
+        let plane = Plane::new();
+        let ray = Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(plane.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_not_intersect_a_coplanar_ray() {
+        let plane = Plane::new();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert!(plane.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_intersect_a_ray_from_above() {
+        let plane = Plane::new();
+        let ray = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+
+        let xs = plane.local_intersect(&ray);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+        assert!(std::ptr::addr_eq(xs[0].object, &plane));
+    }
+
+    #[test]
+    fn should_intersect_a_ray_from_below() {
+        let plane = Plane::new();
+        let ray = Ray::new(Point::new(0.0, -1.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+
+        let xs = plane.local_intersect(&ray);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 1.0);
+        assert!(std::ptr::addr_eq(xs[0].object, &plane));
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let bounds = Plane::new().bounds();
+
+        assert_eq!(bounds.min.x, f64::NEG_INFINITY);
+        assert_eq!(bounds.min.y, 0.0);
+        assert_eq!(bounds.min.z, f64::NEG_INFINITY);
+        assert_eq!(bounds.max.x, f64::INFINITY);
+        assert_eq!(bounds.max.y, 0.0);
+        assert_eq!(bounds.max.z, f64::INFINITY);
+    }
+}
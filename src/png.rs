@@ -0,0 +1,650 @@
+use std::io::{self, Write};
+
+/// Bits per color channel in an encoded PNG.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PngDepth {
+    Eight,
+    /// Keeps far more precision than the 8-bit `Color::to_true_color` values.
+    Sixteen,
+}
+
+impl PngDepth {
+    fn bits(self) -> u8 {
+        match self {
+            PngDepth::Eight => 8,
+            PngDepth::Sixteen => 16,
+        }
+    }
+
+    fn bytes_per_pixel(self) -> usize {
+        3 * self.bits() as usize / 8
+    }
+}
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// PNG's color type for truecolor pixels without alpha.
+const COLOR_TYPE_RGB: u8 = 2;
+
+/// The scanline filter that stores each byte as the difference to the same
+/// channel of the pixel on its left, which suits smooth renders well.
+const FILTER_SUB: u8 = 1;
+
+/// Encodes RGB `samples`, row after row with big endian 16-bit channels if
+/// `depth` asks for them, as a PNG image.
+pub fn encode<W: Write>(
+    mut w: W,
+    width: usize,
+    height: usize,
+    depth: PngDepth,
+    samples: &[u8],
+) -> io::Result<()> {
+    let invalid_input = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let size = width
+        .checked_mul(depth.bytes_per_pixel())
+        .and_then(|row_length| row_length.checked_mul(height));
+    let size = match (size, u32::try_from(width), u32::try_from(height)) {
+        (Some(size), Ok(_), Ok(_)) if size > 0 => size,
+        _ => {
+            return Err(invalid_input(format!(
+                "a PNG can't be {}x{} pixels",
+                width, height
+            )))
+        }
+    };
+    if samples.len() != size {
+        return Err(invalid_input(format!(
+            "a {}x{} image takes {} bytes of samples, found {}",
+            width,
+            height,
+            size,
+            samples.len()
+        )));
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    header.extend([depth.bits(), COLOR_TYPE_RGB, 0, 0, 0]);
+
+    let bpp = depth.bytes_per_pixel();
+    let row_length = width * bpp;
+    let mut filtered = Vec::with_capacity(size + height);
+    for row in samples.chunks(row_length) {
+        filtered.push(FILTER_SUB);
+        filtered.extend(row.iter().enumerate().map(|(i, &byte)| {
+            if i < bpp {
+                byte
+            } else {
+                byte.wrapping_sub(row[i - bpp])
+            }
+        }));
+    }
+
+    w.write_all(&SIGNATURE)?;
+    write_chunk(&mut w, b"IHDR", &header)?;
+    write_chunk(&mut w, b"IDAT", &zlib_compress(&filtered))?;
+    write_chunk(&mut w, b"IEND", &[])?;
+    w.flush()
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large"))?;
+
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+
+    w.write_all(&length.to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc.finish().to_be_bytes())
+}
+
+/// The CRC-32 used by PNG and zlib's gzip sibling (polynomial 0xEDB88320).
+pub struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        let mut table = [0; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        Self {
+            table,
+            value: 0xFFFF_FFFF,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.value =
+                self.table[((self.value ^ byte as u32) & 0xFF) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        self.value ^ 0xFFFF_FFFF
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // 5552 bytes is the most that can be summed before a u32 could overflow.
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Wraps a deflate stream in a zlib header and Adler-32 trailer.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // 32K window, deflate, no preset dictionary, fastest compression level.
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const WINDOW_SIZE: usize = 32 * 1024;
+const HASH_BITS: u32 = 15;
+/// How many earlier positions with the same hash are tried before settling.
+const MAX_CHAIN: usize = 64;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Compresses `data` into a single deflate block using the fixed Huffman
+/// codes, finding repeats with a hash chained LZ77 search.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::default();
+    // BFINAL, then BTYPE 01 for fixed Huffman codes.
+    out.write_bits(1, 1);
+    out.write_bits(1, 2);
+
+    let hash = |i: usize| {
+        let key = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+        (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    // Links are only followed within the window, so a ring buffer holds them all.
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = longest_match(data, i, &head, &previous, hash);
+
+        let advance = if length >= MIN_MATCH {
+            write_match(&mut out, length, distance);
+            length
+        } else {
+            write_symbol(&mut out, data[i] as u16);
+            1
+        };
+        for j in (i..i + advance).filter(|j| j + MIN_MATCH <= data.len()) {
+            let h = hash(j);
+            previous[j & (WINDOW_SIZE - 1)] = head[h];
+            head[h] = j;
+        }
+        i += advance;
+    }
+
+    write_symbol(&mut out, 256);
+    out.finish()
+}
+
+fn longest_match(
+    data: &[u8],
+    i: usize,
+    head: &[usize],
+    previous: &[usize],
+    hash: impl Fn(usize) -> usize,
+) -> (usize, usize) {
+    if i + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let (mut best_length, mut best_distance) = (0, 0);
+    let mut candidate = head[hash(i)];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || i - candidate > WINDOW_SIZE {
+            break;
+        }
+
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[i..])
+            .take(MAX_MATCH)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best_length {
+            best_length = length;
+            best_distance = i - candidate;
+            if length == MAX_MATCH {
+                break;
+            }
+        }
+        candidate = previous[candidate & (WINDOW_SIZE - 1)];
+    }
+    (best_length, best_distance)
+}
+
+fn write_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= length)
+        .unwrap();
+    write_symbol(out, 257 + code as u16);
+    out.write_bits(
+        (length - LENGTH_BASE[code] as usize) as u32,
+        LENGTH_EXTRA[code] as u32,
+    );
+
+    let code = DISTANCE_BASE
+        .iter()
+        .rposition(|&base| base as usize <= distance)
+        .unwrap();
+    out.write_huffman(code as u32, 5);
+    out.write_bits(
+        (distance - DISTANCE_BASE[code] as usize) as u32,
+        DISTANCE_EXTRA[code] as u32,
+    );
+}
+
+/// Writes a literal/length symbol with its fixed Huffman code.
+fn write_symbol(out: &mut BitWriter, symbol: u16) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => out.write_huffman(0x30 + symbol, 8),
+        144..=255 => out.write_huffman(0x190 + symbol - 144, 9),
+        256..=279 => out.write_huffman(symbol - 256, 7),
+        _ => out.write_huffman(0xC0 + symbol - 280, 8),
+    }
+}
+
+/// Packs bits least significant first, as deflate expects.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first.
+    fn write_huffman(&mut self, code: u32, length: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A minimal, independent PNG decoder for 8 and 16-bit RGB images,
+    /// returning width, height, depth and unfiltered samples.
+    pub fn decode(png: &[u8]) -> (usize, usize, u8, Vec<u8>) {
+        assert_eq!(png[..8], SIGNATURE);
+
+        let mut position = 8;
+        let mut header = Vec::new();
+        let mut compressed = Vec::new();
+        loop {
+            let length =
+                u32::from_be_bytes(png[position..position + 4].try_into().unwrap()) as usize;
+            let kind = &png[position + 4..position + 8];
+            let data = &png[position + 8..position + 8 + length];
+            let crc = u32::from_be_bytes(
+                png[position + 8 + length..position + 12 + length]
+                    .try_into()
+                    .unwrap(),
+            );
+            assert_eq!(crc, crc32(&png[position + 4..position + 8 + length]));
+            position += 12 + length;
+
+            match kind {
+                b"IHDR" => header = data.to_vec(),
+                b"IDAT" => compressed.extend(data),
+                b"IEND" => break,
+                _ => {}
+            }
+        }
+
+        let width = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let depth = header[8];
+        assert_eq!(header[9..], [COLOR_TYPE_RGB, 0, 0, 0]);
+
+        let raw = zlib_decompress(&compressed);
+        let bpp = 3 * depth as usize / 8;
+        let row_length = width * bpp;
+        assert_eq!(raw.len(), (row_length + 1) * height);
+
+        let mut samples: Vec<u8> = Vec::with_capacity(row_length * height);
+        for (y, row) in raw.chunks(row_length + 1).enumerate() {
+            let (filter, row) = (row[0], &row[1..]);
+            for (x, &byte) in row.iter().enumerate() {
+                let left = if x >= bpp {
+                    samples[y * row_length + x - bpp]
+                } else {
+                    0
+                };
+                let up = if y > 0 {
+                    samples[(y - 1) * row_length + x]
+                } else {
+                    0
+                };
+                let up_left = if x >= bpp && y > 0 {
+                    samples[(y - 1) * row_length + x - bpp]
+                } else {
+                    0
+                };
+                let predictor = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    4 => paeth(left, up, up_left),
+                    _ => panic!("unknown filter {}", filter),
+                };
+                samples.push(byte.wrapping_add(predictor));
+            }
+        }
+
+        (width, height, depth, samples)
+    }
+
+    fn paeth(a: u8, b: u8, c: u8) -> u8 {
+        let p = a as i16 + b as i16 - c as i16;
+        let (pa, pb, pc) = (
+            (p - a as i16).abs(),
+            (p - b as i16).abs(),
+            (p - c as i16).abs(),
+        );
+        if pa <= pb && pa <= pc {
+            a
+        } else if pb <= pc {
+            b
+        } else {
+            c
+        }
+    }
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        position: usize,
+    }
+
+    impl BitReader<'_> {
+        fn bit(&mut self) -> u32 {
+            let bit = (self.data[self.position / 8] >> (self.position % 8)) & 1;
+            self.position += 1;
+            bit as u32
+        }
+
+        fn bits(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |value, i| value | self.bit() << i)
+        }
+
+        fn huffman(&mut self, length: u32) -> u32 {
+            (0..length).fold(0, |code, _| code << 1 | self.bit())
+        }
+    }
+
+    /// Inflates stored and fixed Huffman blocks, checking the zlib wrapper.
+    fn zlib_decompress(data: &[u8]) -> Vec<u8> {
+        assert_eq!((data[0] as u16 * 256 + data[1] as u16) % 31, 0);
+        assert_eq!(data[0] & 0x0F, 8);
+
+        let mut reader = BitReader {
+            data: &data[2..data.len() - 4],
+            position: 0,
+        };
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = reader.bits(1) == 1;
+            match reader.bits(2) {
+                0 => {
+                    reader.position = reader.position.div_ceil(8) * 8;
+                    let start = reader.position / 8;
+                    let length = u16::from_le_bytes([reader.data[start], reader.data[start + 1]]);
+                    out.extend(&reader.data[start + 4..start + 4 + length as usize]);
+                    reader.position = (start + 4 + length as usize) * 8;
+                }
+                1 => loop {
+                    let mut code = reader.huffman(7);
+                    let symbol = if code <= 0b0010111 {
+                        256 + code
+                    } else {
+                        code = code << 1 | reader.bit();
+                        match code {
+                            0x30..=0xBF => code - 0x30,
+                            0xC0..=0xC7 => 280 + code - 0xC0,
+                            _ => 144 + (code << 1 | reader.bit()) - 0x190,
+                        }
+                    };
+
+                    match symbol {
+                        0..=255 => out.push(symbol as u8),
+                        256 => break,
+                        _ => {
+                            let index = (symbol - 257) as usize;
+                            let length = LENGTH_BASE[index] as usize
+                                + reader.bits(LENGTH_EXTRA[index] as u32) as usize;
+                            let index = reader.huffman(5) as usize;
+                            let distance = DISTANCE_BASE[index] as usize
+                                + reader.bits(DISTANCE_EXTRA[index] as u32) as usize;
+                            for _ in 0..length {
+                                out.push(out[out.len() - distance]);
+                            }
+                        }
+                    }
+                },
+                kind => panic!("unsupported block type {}", kind),
+            }
+            if last {
+                break;
+            }
+        }
+
+        let expected = u32::from_be_bytes(data[data.len() - 4..].try_into().unwrap());
+        assert_eq!(adler32(&out), expected);
+        out
+    }
+
+    #[test]
+    fn should_compute_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn should_compute_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&vec![0xFF; 100_000]), 0x149A_302C);
+    }
+
+    /// Streams from CPython's zlib with `Z_FIXED`. Without repeats to find,
+    /// both encoders must produce the same literal codes.
+    #[test]
+    fn should_match_zlib_byte_for_byte() {
+        let cases: [(&[u8], &[u8]); 3] = [
+            (b"", &[0x78, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01]),
+            (
+                b"a",
+                &[0x78, 0x01, 0x4B, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62],
+            ),
+            (
+                b"Hello, PNG!",
+                &[
+                    0x78, 0x01, 0xF3, 0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0x08, 0xF0, 0x73, 0x57,
+                    0x04, 0x00, 0x15, 0xCB, 0x03, 0x47,
+                ],
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(zlib_compress(input), expected);
+            assert_eq!(zlib_decompress(expected), input);
+        }
+    }
+
+    /// zlib splits the repeats into different matches, so its stream is only
+    /// decoded. Ours is pinned to bytes CPython's `zlib.decompress` accepts.
+    #[test]
+    fn should_agree_with_zlib_on_matches() {
+        let input = b"abcabcabcabc";
+        let from_zlib = [
+            0x78, 0x01, 0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x21, 0x00, 0x1D, 0xE0, 0x04, 0x99,
+        ];
+
+        assert_eq!(zlib_decompress(&from_zlib), input);
+        assert_eq!(
+            zlib_compress(input),
+            [0x78, 0x01, 0x4B, 0x4C, 0x4A, 0x86, 0x23, 0x00, 0x1D, 0xE0, 0x04, 0x99]
+        );
+    }
+
+    /// A 2x1 image with pixels (10, 20, 30) and (15, 20, 25), assembled by
+    /// hand with CPython's zlib and crc32.
+    #[test]
+    fn should_encode_a_known_png() {
+        let expected = [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00,
+            0x00, 0x7B, 0x40, 0xE8, 0xDD, 0x00, 0x00, 0x00, 0x0F, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0x01, 0x63, 0xE4, 0x12, 0x91, 0x63, 0x65, 0xF8, 0x0D, 0x00, 0x02, 0x30, 0x01, 0x3E,
+            0x0A, 0x9F, 0xA7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42,
+            0x60, 0x82,
+        ];
+        let mut png = Vec::new();
+
+        encode(&mut png, 2, 1, PngDepth::Eight, &[10, 20, 30, 15, 20, 25]).unwrap();
+
+        assert_eq!(png, expected);
+    }
+
+    #[test]
+    fn should_round_trip_through_deflate() {
+        let inputs: Vec<Vec<u8>> = vec![
+            Vec::new(),
+            b"a".to_vec(),
+            b"abcabcabcabcabcabcabcabcabc".to_vec(),
+            vec![7; 1000],
+            (0..70_000u64).map(|i| (i * i % 251) as u8).collect(),
+            (0..100_000u32).map(|i| (i / 300) as u8).collect(),
+            // Repeats exactly one window apart, the farthest a match may reach.
+            (0..2 * WINDOW_SIZE as u64)
+                .map(|i| (((i % WINDOW_SIZE as u64) * 2_654_435_761) >> 13) as u8)
+                .collect(),
+        ];
+
+        for input in inputs {
+            let compressed = zlib_compress(&input);
+            assert_eq!(zlib_decompress(&compressed), input);
+        }
+    }
+
+    #[test]
+    fn should_compress_repetitive_data() {
+        let input = vec![42; 10_000];
+
+        assert!(zlib_compress(&input).len() < 100);
+    }
+
+    #[test]
+    fn should_round_trip_png_samples() {
+        let samples: Vec<u8> = (0..5 * 4 * 6).map(|i| (i * 37 % 256) as u8).collect();
+        let mut png = Vec::new();
+
+        encode(&mut png, 5, 4, PngDepth::Sixteen, &samples).unwrap();
+
+        assert_eq!(decode(&png), (5, 4, 16, samples));
+    }
+
+    #[test]
+    fn should_refuse_empty_images() {
+        let error = encode(Vec::new(), 0, 3, PngDepth::Eight, &[]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn should_refuse_sizes_that_do_not_match_the_samples() {
+        let cases = [
+            (2, 2, PngDepth::Eight, 11),
+            (2, 2, PngDepth::Sixteen, 12),
+            (usize::MAX / 2, 1, PngDepth::Eight, 0),
+            (1 << 20, 1 << 20, PngDepth::Sixteen, 0),
+        ];
+
+        for (width, height, depth, length) in cases {
+            let error = encode(Vec::new(), width, height, depth, &vec![0; length]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use std::fmt;
+use std::io::{self, Read};
+
+#[derive(Debug)]
+pub enum PpmError {
+    Io(io::Error),
+    /// The magic number is not one of P2, P3, P5 or P6.
+    UnsupportedFormat(String),
+    Header(String),
+    Sample {
+        index: usize,
+        message: String,
+    },
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::Io(error) => write!(f, "could not read image: {}", error),
+            PpmError::UnsupportedFormat(magic) => {
+                write!(
+                    f,
+                    "unsupported image format {:?}, expected P2, P3, P5 or P6",
+                    magic
+                )
+            }
+            PpmError::Header(message) => write!(f, "invalid header: {}", message),
+            PpmError::Sample { index, message } => write!(f, "sample {}: {}", index, message),
+            PpmError::Truncated { expected, found } => write!(
+                f,
+                "image data is truncated, expected {} samples but found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+impl From<io::Error> for PpmError {
+    fn from(error: io::Error) -> Self {
+        PpmError::Io(error)
+    }
+}
+
+/// Splits the text parts of a netpbm file into tokens, skipping comments.
+struct Tokens<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match self.data.get(self.position)? {
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                b'#' => {
+                    while self
+                        .data
+                        .get(self.position)
+                        .is_some_and(|&byte| byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        let start = self.position;
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|&byte| !byte.is_ascii_whitespace() && byte != b'#')
+        {
+            self.position += 1;
+        }
+        // Anything that isn't ASCII can't parse as a number anyway.
+        Some(std::str::from_utf8(&self.data[start..self.position]).unwrap_or("?"))
+    }
+
+    fn header_value(&mut self, name: &str) -> Result<usize, PpmError> {
+        let token = self
+            .next()
+            .ok_or_else(|| PpmError::Header(format!("missing {}", name)))?;
+        token
+            .parse()
+            .map_err(|_| PpmError::Header(format!("{} {:?} is not a number", name, token)))
+    }
+}
+
+impl Canvas {
+    /// Reads a PPM (P3, P6) or PGM (P2, P5) image. Gray images become gray colors.
+    pub fn from_ppm<R: Read>(mut reader: R) -> Result<Canvas, PpmError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut tokens = Tokens {
+            data: &data,
+            position: 0,
+        };
+
+        let magic = tokens.next().unwrap_or_default();
+        let (channels, binary) = match magic {
+            "P2" => (1, false),
+            "P3" => (3, false),
+            "P5" => (1, true),
+            "P6" => (3, true),
+            _ => return Err(PpmError::UnsupportedFormat(magic.to_string())),
+        };
+
+        let width = tokens.header_value("width")?;
+        let height = tokens.header_value("height")?;
+        let maxval = tokens.header_value("maximum value")?;
+        if !(1..=65535).contains(&maxval) {
+            return Err(PpmError::Header(format!(
+                "maximum value {} is not between 1 and 65535",
+                maxval
+            )));
+        }
+
+        let expected = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| PpmError::Header(format!("{}x{} is too large", width, height)))?;
+
+        let samples = if binary {
+            // Exactly one whitespace character separates the header from the raster.
+            let raster = data.get(tokens.position + 1..).unwrap_or_default();
+            binary_samples(raster, maxval, expected)?
+        } else {
+            ascii_samples(&mut tokens, maxval, expected)?
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = maxval as f64;
+        for (index, pixel) in samples.chunks(channels).enumerate() {
+            let color = match pixel {
+                &[gray] => Color::new(gray, gray, gray),
+                rgb => Color::new(rgb[0], rgb[1], rgb[2]),
+            };
+            canvas.set_pixel(index % width, index / width, color * (1.0 / scale));
+        }
+        Ok(canvas)
+    }
+}
+
+fn ascii_samples(
+    tokens: &mut Tokens,
+    maxval: usize,
+    expected: usize,
+) -> Result<Vec<f64>, PpmError> {
+    // The header can claim any size, but every sample takes at least a digit
+    // and a separator of the input that is actually there.
+    let remaining = tokens.data.len().saturating_sub(tokens.position);
+    let mut samples = Vec::with_capacity(expected.min(remaining / 2 + 1));
+    while samples.len() < expected {
+        let index = samples.len();
+        let token = tokens.next().ok_or(PpmError::Truncated {
+            expected,
+            found: index,
+        })?;
+        let value: usize = token.parse().map_err(|_| PpmError::Sample {
+            index,
+            message: format!("{:?} is not a number", token),
+        })?;
+        samples.push(check_sample(value, maxval, index)?);
+    }
+    Ok(samples)
+}
+
+fn binary_samples(raster: &[u8], maxval: usize, expected: usize) -> Result<Vec<f64>, PpmError> {
+    let bytes_per_sample = if maxval > 255 { 2 } else { 1 };
+    let raster_size = expected
+        .checked_mul(bytes_per_sample)
+        .ok_or_else(|| PpmError::Header(format!("{} samples is too large", expected)))?;
+    if raster.len() < raster_size {
+        return Err(PpmError::Truncated {
+            expected,
+            found: raster.len() / bytes_per_sample,
+        });
+    }
+
+    raster
+        .chunks(bytes_per_sample)
+        .take(expected)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let value = bytes
+                .iter()
+                .fold(0, |value, &byte| value << 8 | byte as usize);
+            check_sample(value, maxval, index)
+        })
+        .collect()
+}
+
+fn check_sample(value: usize, maxval: usize, index: usize) -> Result<f64, PpmError> {
+    if value > maxval {
+        return Err(PpmError::Sample {
+            index,
+            message: format!("{} is larger than the maximum value {}", value, maxval),
+        });
+    }
+    Ok(value as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::PpmFormat;
+
+    fn read(ppm: &[u8]) -> Result<Canvas, PpmError> {
+        Canvas::from_ppm(ppm)
+    }
+
+    #[test]
+    fn should_fail_on_an_unknown_magic_number() {
+        let ppm = b"P32\n1 1\n255\n0 0 0\n";
+
+        assert!(matches!(read(ppm), Err(PpmError::UnsupportedFormat(magic)) if magic == "P32"));
+    }
+
+    #[test]
+    fn should_read_width_and_height() {
+        let canvas = read(b"P3\n10 2\n255\n").map(|_| ());
+
+        assert!(matches!(
+            canvas,
+            Err(PpmError::Truncated {
+                expected: 60,
+                found: 0
+            })
+        ));
+
+        let canvas = read(b"P3\n1 2\n255\n0 0 0 0 0 0\n").unwrap();
+        assert_eq!((canvas.width(), canvas.height), (1, 2));
+    }
+
+    #[test]
+    fn should_read_pixel_data() {
+        let ppm = b"P3\n4 3\n255\n255 127 0  0 127 255  127 255 0  255 255 255\n0 0 0  255 0 0  0 255 0  0 0 255\n255 255 0  0 255 255  255 0 255  127 127 127\n";
+
+        let canvas = read(ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 127.0 / 255.0, 0.0));
+        assert_eq!(canvas.get_pixel(1, 1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            canvas.get_pixel(3, 2),
+            Color::new(0.49804, 0.49804, 0.49804)
+        );
+    }
+
+    #[test]
+    fn should_ignore_comments_and_odd_whitespace() {
+        let ppm = b"P3 # a comment\r\n# another\n2\t1#right after a token\n  255\n\n255 255 255 # white\r\n0\t0   0\n";
+
+        let canvas = read(ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(canvas.get_pixel(1, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_allow_pixel_data_across_lines() {
+        let ppm = b"P3\n1 1\n255\n51\n153\n\n204\n";
+
+        let canvas = read(ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn should_scale_by_an_arbitrary_maximum_value() {
+        let ppm = b"P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+
+        let canvas = read(ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 1), Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn should_reject_samples_above_the_maximum_value() {
+        let ppm = b"P3\n1 1\n100\n100 101 0\n";
+
+        assert!(matches!(read(ppm), Err(PpmError::Sample { index: 1, .. })));
+    }
+
+    #[test]
+    fn should_reject_a_bad_header() {
+        assert!(matches!(
+            read(b"P3\nten 2\n255\n"),
+            Err(PpmError::Header(_))
+        ));
+        assert!(matches!(
+            read(b"P3\n1 1\n0\n0 0 0"),
+            Err(PpmError::Header(_))
+        ));
+        assert!(matches!(read(b"P6\n1 1"), Err(PpmError::Header(_))));
+        assert!(matches!(read(b""), Err(PpmError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn should_read_binary_pixel_data() {
+        let mut ppm = b"P6\n# binary\n2 1\n255\n".to_vec();
+        ppm.extend([255, 0, 51, b'\n', b' ', 0]);
+
+        let canvas = read(&ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 0.0, 0.2));
+        assert_eq!(
+            canvas.get_pixel(1, 0),
+            Color::new(10.0 / 255.0, 32.0 / 255.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_read_16_bit_binary_pixel_data() {
+        let mut ppm = b"P6 1 1 65535\n".to_vec();
+        ppm.extend([0xFF, 0xFF, 0x80, 0x00, 0x00, 0x00]);
+
+        let canvas = read(&ppm).unwrap();
+
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 0.50001, 0.0));
+    }
+
+    #[test]
+    fn should_fail_on_truncated_binary_data() {
+        let mut ppm = b"P6\n2 2\n255\n".to_vec();
+        ppm.extend([1, 2, 3, 4, 5]);
+
+        assert!(matches!(
+            read(&ppm),
+            Err(PpmError::Truncated {
+                expected: 12,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn should_not_trust_the_size_in_the_header() {
+        assert!(matches!(
+            read(b"P2\n1000000000 1000000000\n255\n1\n"),
+            Err(PpmError::Truncated { found: 1, .. })
+        ));
+        assert!(matches!(
+            read(b"P6\n4000000000 1000000000\n65535\n"),
+            Err(PpmError::Header(_))
+        ));
+        assert!(matches!(
+            read(b"P5\n100000 100000\n255\n\x01"),
+            Err(PpmError::Truncated { found: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn should_read_gray_images() {
+        let canvas = read(b"P2\n2 1\n4\n1 4\n").unwrap();
+        assert_eq!(canvas.get_pixel(0, 0), Color::new(0.25, 0.25, 0.25));
+
+        let canvas = read(b"P5\n2 1\n255\n\x00\xff").unwrap();
+        assert_eq!(canvas.get_pixel(1, 0), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn should_round_trip_written_images() {
+        let mut canvas = Canvas::new(13, 4);
+        for y in 0..4 {
+            for x in 0..13 {
+                let value = ((x * 4 + y) * 5) as f64 / 255.0;
+                canvas.set_pixel(x, y, Color::new(value, 1.0 - value, 0.6));
+            }
+        }
+
+        for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+            let mut ppm = Vec::new();
+            canvas.write_ppm(&mut ppm, format).unwrap();
+            let read_back = read(&ppm).unwrap();
+
+            for y in 0..4 {
+                for x in 0..13 {
+                    let bytes = canvas.get_pixel(x, y).to_true_color();
+                    let expected = Color::new(bytes[0] as f64, bytes[1] as f64, bytes[2] as f64)
+                        * (1.0 / 255.0);
+                    assert_eq!(read_back.get_pixel(x, y), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_describe_errors() {
+        let error = read(b"P3\n2 2\n255\n0 0 0").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "image data is truncated, expected 12 samples but found 3"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::vector::Vector;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Vector,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Vector) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn position(&self, t: f64) -> Point {
+        self.origin + self.direction * t
+    }
+
+    pub fn transform(&self, matrix: &Matrix<4>) -> Self {
+        Self {
+            origin: *matrix * self.origin,
+            direction: *matrix * self.direction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_create_and_query_a_ray() {
+        let origin = Point::new(1.0, 2.0, 3.0);
+        let direction = Vector::new(4.0, 5.0, 6.0);
+
+        let ray = Ray::new(origin, direction);
+
+        assert_eq!(ray.origin, origin);
+        assert_eq!(ray.direction, direction);
+    }
+
+    #[test]
+    fn should_compute_a_point_from_a_distance() {
+        let ray = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+
+        assert_eq!(ray.position(0.0), Point::new(2.0, 3.0, 4.0));
+        assert_eq!(ray.position(1.0), Point::new(3.0, 3.0, 4.0));
+        assert_eq!(ray.position(-1.0), Point::new(1.0, 3.0, 4.0));
+        assert_eq!(ray.position(2.5), Point::new(4.5, 3.0, 4.0));
+    }
+
+    #[test]
+    fn should_translate_a_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+        let m = Matrix::translation(3.0, 4.0, 5.0);
+
+        let actual = ray.transform(&m);
+
+        assert_eq!(actual.origin, Point::new(4.0, 6.0, 8.0));
+        assert_eq!(actual.direction, Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn should_scale_a_ray() {
+        let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+        let m = Matrix::scaling(2.0, 3.0, 4.0);
+
+        let actual = ray.transform(&m);
+
+        assert_eq!(actual.origin, Point::new(2.0, 6.0, 12.0));
+        assert_eq!(actual.direction, Vector::new(0.0, 3.0, 0.0));
+    }
+}
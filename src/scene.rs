@@ -0,0 +1,1001 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::cone::Cone;
+use crate::csg::{Csg, Operation};
+use crate::cube::Cube;
+use crate::cylinder::Cylinder;
+use crate::group::Group;
+use crate::light::PointLight;
+use crate::material::Material;
+use crate::obj_file::ObjFile;
+use crate::pattern::{
+    CheckersPattern, GradientPattern, Pattern, RadialGradientPattern, RingPattern, StripePattern,
+};
+use crate::plane::Plane;
+use crate::point::Point;
+use crate::shape::Shape;
+use crate::sphere::Sphere;
+use crate::texture::{TextureMapPattern, UvImage, UvMapping};
+use crate::transform::{InvertibleTransform, Transform};
+use crate::vector::Vector;
+use crate::world::World;
+use crate::yaml::{self, Node, Value, YamlError};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How deeply definitions may refer to other definitions, to catch cycles.
+const MAX_DEFINITION_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Yaml(YamlError),
+    /// Well-formed YAML that doesn't describe a valid scene.
+    Invalid {
+        line: usize,
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "could not read scene: {}", error),
+            SceneError::Yaml(error) => write!(f, "{}", error),
+            SceneError::Invalid { line, key, message } => {
+                write!(f, "line {}, key `{}`: {}", line, key, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+impl From<YamlError> for SceneError {
+    fn from(error: YamlError) -> Self {
+        SceneError::Yaml(error)
+    }
+}
+
+fn invalid<T>(node: &Node, key: &str, message: impl Into<String>) -> Result<T, SceneError> {
+    Err(SceneError::Invalid {
+        line: node.line,
+        key: key.to_string(),
+        message: message.into(),
+    })
+}
+
+/// A camera and the world it looks at, read from a YAML scene description.
+///
+/// A scene is a list of `add` items for the camera, lights and shapes, and
+/// `define` items naming materials or transforms for later use. A definition
+/// can `extend` an earlier one, overriding some of its keys:
+///
+/// ```yaml
+/// - add: camera
+///   width: 100
+///   height: 50
+///   field-of-view: 0.785
+///   from: [0, 1.5, -5]
+///   to: [0, 1, 0]
+///   up: [0, 1, 0]
+///
+/// - add: light
+///   at: [-10, 10, -10]
+///   intensity: [1, 1, 1]
+///
+/// - define: shiny
+///   value:
+///     specular: 1
+///
+/// - add: sphere
+///   material:
+///     extend: shiny
+///     color: [1, 0.2, 0.2]
+///   transform:
+///     - [scale, 0.5, 0.5, 0.5]
+///     - [translate, 0, 0.5, 0]
+/// ```
+///
+/// Transforms are applied in the order they are listed.
+#[derive(Debug)]
+pub struct Scene {
+    pub camera: Camera,
+    pub world: World,
+}
+
+impl Scene {
+    /// Reads a scene file. Paths inside it are relative to the file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        let base = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Self::load(&source, base)
+    }
+
+    /// Parses a scene. Paths inside it are relative to the working directory.
+    pub fn parse(source: &str) -> Result<Self, SceneError> {
+        Self::load(source, PathBuf::from("."))
+    }
+
+    fn load(source: &str, base: PathBuf) -> Result<Self, SceneError> {
+        let document = yaml::parse(source)?;
+        let mut loader = Loader {
+            base,
+            definitions: HashMap::new(),
+        };
+
+        let Some(items) = document.as_sequence() else {
+            return invalid(
+                &document,
+                "",
+                "a scene must be a list of `add` and `define` items",
+            );
+        };
+
+        let mut camera = None;
+        let mut world = World::new();
+        for item in items {
+            if let Some(name) = item.get("define") {
+                loader.define(item, name)?;
+                continue;
+            }
+
+            let Some(kind) = item.get("add") else {
+                return invalid(item, "add", "expected an `add` or `define` item");
+            };
+            match string(kind, "add")? {
+                "camera" => {
+                    if camera.is_some() {
+                        return invalid(kind, "add", "a scene can only have one camera");
+                    }
+                    camera = Some(loader.camera(item)?);
+                }
+                "light" => world.add_light(loader.light(item)?),
+                _ => world.add_boxed_object(loader.shape(item)?),
+            }
+        }
+
+        match camera {
+            Some(camera) => Ok(Scene { camera, world }),
+            None => invalid(&document, "add", "the scene has no camera"),
+        }
+    }
+}
+
+struct Loader {
+    base: PathBuf,
+    definitions: HashMap<String, Node>,
+}
+
+impl Loader {
+    fn define(&mut self, item: &Node, name: &Node) -> Result<(), SceneError> {
+        check_keys(item, &["define", "extend", "value"])?;
+        let name = string(name, "define")?.to_string();
+        let Some(value) = item.get("value") else {
+            return invalid(item, "value", format!("definition `{}` has no value", name));
+        };
+
+        let value = match item.get("extend") {
+            Some(extend) => {
+                let base = self.lookup(extend, "extend", 0)?;
+                let (Some(base_entries), Some(entries)) = (base.as_mapping(), value.as_mapping())
+                else {
+                    return invalid(extend, "extend", "only mappings can be extended");
+                };
+                Node {
+                    line: value.line,
+                    value: Value::Mapping(merge(base_entries, entries)),
+                }
+            }
+            None => value.clone(),
+        };
+
+        self.definitions.insert(name, value);
+        Ok(())
+    }
+
+    /// The definition named by `node`.
+    fn lookup(&self, node: &Node, key: &str, depth: usize) -> Result<&Node, SceneError> {
+        if depth > MAX_DEFINITION_DEPTH {
+            return invalid(node, key, "definitions refer to each other too deeply");
+        }
+        let name = string(node, key)?;
+        match self.definitions.get(name) {
+            Some(definition) => Ok(definition),
+            None => invalid(node, key, format!("`{}` is not defined", name)),
+        }
+    }
+
+    fn camera(&self, item: &Node) -> Result<Camera, SceneError> {
+        check_keys(
+            item,
+            &[
+                "add",
+                "width",
+                "height",
+                "field-of-view",
+                "from",
+                "to",
+                "up",
+            ],
+        )?;
+
+        let width = size(required(item, "width")?, "width")?;
+        let height = size(required(item, "height")?, "height")?;
+        let field_of_view = number(required(item, "field-of-view")?, "field-of-view")?;
+        let [x, y, z] = triple(required(item, "from")?, "from")?;
+        let from = Point::new(x, y, z);
+        let [x, y, z] = triple(required(item, "to")?, "to")?;
+        let to = Point::new(x, y, z);
+        let [x, y, z] = triple(required(item, "up")?, "up")?;
+        let up = Vector::new(x, y, z);
+
+        let mut camera = Camera::new(width, height, field_of_view);
+        let view = Transform::identity().view(from, to, up);
+        match InvertibleTransform::try_from(view) {
+            Ok(view) => camera.set_invertible_transform(view),
+            Err(_) => {
+                return invalid(
+                    item,
+                    "to",
+                    "the camera can't look from `from` to `to` with this `up`, \
+                     they must be distinct points and `up` must not point along the view",
+                )
+            }
+        }
+        Ok(camera)
+    }
+
+    fn light(&self, item: &Node) -> Result<PointLight, SceneError> {
+        check_keys(item, &["add", "at", "intensity", "shadow"])?;
+
+        let [x, y, z] = triple(required(item, "at")?, "at")?;
+        let intensity = color(required(item, "intensity")?, "intensity")?;
+        let mut light = PointLight::new(Point::new(x, y, z), intensity);
+        if let Some(shadow) = item.get("shadow") {
+            light.casts_shadows = boolean(shadow, "shadow")?;
+        }
+        Ok(light)
+    }
+
+    fn shape(&self, item: &Node) -> Result<Box<dyn Shape>, SceneError> {
+        let Some(kind_node) = item.get("add") else {
+            return invalid(item, "add", "expected an `add` item");
+        };
+        let kind = string(kind_node, "add")?;
+
+        let (mut shape, own_keys): (Box<dyn Shape>, &[&str]) = match kind {
+            "sphere" => (Box::new(Sphere::new()), &[]),
+            "plane" => (Box::new(Plane::new()), &[]),
+            "cube" => (Box::new(Cube::new()), &[]),
+            "cylinder" => {
+                let (minimum, maximum, closed) = truncation(item)?;
+                (
+                    Box::new(Cylinder::truncated(minimum, maximum, closed)),
+                    &["min", "max", "closed"],
+                )
+            }
+            "cone" => {
+                let (minimum, maximum, closed) = truncation(item)?;
+                (
+                    Box::new(Cone::truncated(minimum, maximum, closed)),
+                    &["min", "max", "closed"],
+                )
+            }
+            "group" => {
+                let mut group = Group::new();
+                if let Some(children) = item.get("children") {
+                    let Some(children) = children.as_sequence() else {
+                        return invalid(children, "children", "expected a list of objects");
+                    };
+                    for child in children {
+                        group.add_boxed_child(self.shape(child)?);
+                    }
+                }
+                (Box::new(group), &["children"])
+            }
+            "obj" => {
+                let file = required(item, "file")?;
+                let path = self.base.join(string(file, "file")?);
+                match ObjFile::open(&path) {
+                    Ok(obj) => (Box::new(obj.into_group()), &["file"]),
+                    Err(error) => {
+                        return invalid(file, "file", format!("{}: {}", path.display(), error))
+                    }
+                }
+            }
+            "csg" => {
+                let operation_node = required(item, "operation")?;
+                let operation = match string(operation_node, "operation")? {
+                    "union" => Operation::Union,
+                    "intersection" => Operation::Intersection,
+                    "difference" => Operation::Difference,
+                    other => {
+                        let message = format!(
+                            "unknown operation `{}`, expected union, intersection or difference",
+                            other
+                        );
+                        return invalid(operation_node, "operation", message);
+                    }
+                };
+                let left = self.shape(required(item, "left")?)?;
+                let right = self.shape(required(item, "right")?)?;
+                (
+                    Box::new(Csg::from_boxed(operation, left, right)),
+                    &["operation", "left", "right"],
+                )
+            }
+            _ => {
+                return invalid(
+                    kind_node,
+                    "add",
+                    format!("unknown kind of object `{}`", kind),
+                )
+            }
+        };
+
+        let mut allowed = vec!["add", "material", "transform", "shadow"];
+        allowed.extend(own_keys);
+        check_keys(item, &allowed)?;
+
+        if let Some(material) = item.get("material") {
+            shape.set_material(self.material(material, 0)?);
+        }
+        if let Some(transform) = item.get("transform") {
+            let object = format!("`{}`", kind);
+            shape.set_invertible_transform(self.transform(transform, "transform", &object)?);
+        }
+        if let Some(shadow) = item.get("shadow") {
+            shape.set_casts_shadow(boolean(shadow, "shadow")?);
+        }
+        Ok(shape)
+    }
+
+    /// Reads a material, `depth` levels of `extend` away from the object using it.
+    fn material(&self, node: &Node, depth: usize) -> Result<Material, SceneError> {
+        let node = match node.value {
+            Value::Scalar(_) => self.lookup(node, "material", depth)?,
+            _ => node,
+        };
+        let Some(entries) = node.as_mapping() else {
+            return invalid(
+                node,
+                "material",
+                "expected a mapping or the name of a definition",
+            );
+        };
+
+        // An inline material may extend a defined one, like definitions do.
+        let mut material = match node.get("extend") {
+            Some(extend) => self.material(self.lookup(extend, "extend", depth)?, depth + 1)?,
+            None => Material::new(),
+        };
+        for (key, value) in entries {
+            match key.as_str() {
+                "extend" => {}
+                "color" => material.color = color(value, key)?,
+                "pattern" => material.pattern = Some(self.pattern(value)?),
+                "ambient" => material.ambient = number(value, key)?,
+                "diffuse" => material.diffuse = number(value, key)?,
+                "specular" => material.specular = number(value, key)?,
+                "shininess" => material.shininess = number(value, key)?,
+                "reflective" => material.reflective = number(value, key)?,
+                "transparency" => material.transparency = number(value, key)?,
+                "refractive-index" => material.refractive_index = number(value, key)?,
+                _ => return invalid(value, key, "unknown material property"),
+            }
+        }
+        Ok(material)
+    }
+
+    fn pattern(&self, node: &Node) -> Result<Arc<dyn Pattern>, SceneError> {
+        let kind_node = required(node, "type")?;
+        let kind = string(kind_node, "type")?;
+
+        let mut pattern: Box<dyn Pattern> = if kind == "image" {
+            check_keys(node, &["type", "file", "mapping", "transform"])?;
+            let file = required(node, "file")?;
+            let path = self.base.join(string(file, "file")?);
+            let image = match UvImage::open(&path) {
+                Ok(image) => image,
+                Err(error) => {
+                    return invalid(file, "file", format!("{}: {}", path.display(), error))
+                }
+            };
+            let mapping_node = required(node, "mapping")?;
+            let mapping = match string(mapping_node, "mapping")? {
+                "spherical" => UvMapping::Spherical,
+                "planar" => UvMapping::Planar,
+                "cylindrical" => UvMapping::Cylindrical,
+                other => {
+                    return invalid(
+                        mapping_node,
+                        "mapping",
+                        format!(
+                            "unknown mapping `{}`, expected spherical, planar or cylindrical",
+                            other
+                        ),
+                    )
+                }
+            };
+            Box::new(TextureMapPattern::new(Box::new(image), mapping))
+        } else {
+            check_keys(node, &["type", "colors", "transform"])?;
+            let colors_node = required(node, "colors")?;
+            let [a, b] = match colors_node.as_sequence() {
+                Some([a, b]) => [color(a, "colors")?, color(b, "colors")?],
+                _ => return invalid(colors_node, "colors", "expected a list of two colors"),
+            };
+            match kind {
+                "stripes" => Box::new(StripePattern::new(a, b)),
+                "gradient" => Box::new(GradientPattern::new(a, b)),
+                "rings" => Box::new(RingPattern::new(a, b)),
+                "checkers" => Box::new(CheckersPattern::new(a, b)),
+                "radial-gradient" => Box::new(RadialGradientPattern::new(a, b)),
+                _ => {
+                    return invalid(kind_node, "type", format!("unknown pattern `{}`", kind));
+                }
+            }
+        };
+
+        if let Some(transform) = node.get("transform") {
+            let object = format!("the `{}` pattern", kind);
+            pattern.set_invertible_transform(self.transform(transform, "transform", &object)?);
+        }
+        Ok(Arc::from(pattern))
+    }
+
+    /// Reads the transform of `object`, which names it in errors.
+    fn transform(
+        &self,
+        node: &Node,
+        key: &str,
+        object: &str,
+    ) -> Result<InvertibleTransform, SceneError> {
+        let mut transform = Transform::identity();
+        self.apply_transforms(node, key, &mut transform, 0)?;
+        match InvertibleTransform::try_from(transform) {
+            Ok(transform) => Ok(transform),
+            Err(error) => invalid(node, key, format!("{}: {}", object, error)),
+        }
+    }
+
+    /// Applies every operation in `node` after those already in `transform`.
+    fn apply_transforms(
+        &self,
+        node: &Node,
+        key: &str,
+        transform: &mut Transform,
+        depth: usize,
+    ) -> Result<(), SceneError> {
+        if node.as_str().is_some() {
+            let definition = self.lookup(node, key, depth)?;
+            return self.apply_transforms(definition, key, transform, depth + 1);
+        }
+        let Some(operations) = node.as_sequence() else {
+            return invalid(node, key, "expected a list of transformations");
+        };
+
+        for operation in operations {
+            if operation.as_str().is_some() {
+                let definition = self.lookup(operation, key, depth)?;
+                self.apply_transforms(definition, key, transform, depth + 1)?;
+                continue;
+            }
+
+            let Some((name, arguments)) = operation.as_sequence().and_then(|op| op.split_first())
+            else {
+                return invalid(operation, key, "expected [operation, values...]");
+            };
+            let name = string(name, key)?;
+            let values = arguments
+                .iter()
+                .map(|argument| number(argument, key))
+                .collect::<Result<Vec<f64>, _>>()?;
+
+            *transform = match (name, values.as_slice()) {
+                ("translate", &[x, y, z]) => transform.translate(x, y, z),
+                ("scale", &[x, y, z]) => transform.scale(x, y, z),
+                ("rotate-x", &[r]) => transform.rotate_x(r),
+                ("rotate-y", &[r]) => transform.rotate_y(r),
+                ("rotate-z", &[r]) => transform.rotate_z(r),
+                ("shear", &[xy, xz, yx, yz, zx, zy]) => transform.shear(xy, xz, yx, yz, zx, zy),
+                ("translate" | "scale", _) => return wrong_count(operation, key, name, 3, &values),
+                ("rotate-x" | "rotate-y" | "rotate-z", _) => {
+                    return wrong_count(operation, key, name, 1, &values)
+                }
+                ("shear", _) => return wrong_count(operation, key, name, 6, &values),
+                _ => return invalid(operation, key, format!("unknown transformation `{}`", name)),
+            };
+        }
+        Ok(())
+    }
+}
+
+fn wrong_count<T>(
+    node: &Node,
+    key: &str,
+    name: &str,
+    expected: usize,
+    values: &[f64],
+) -> Result<T, SceneError> {
+    invalid(
+        node,
+        key,
+        format!(
+            "`{}` takes {} values, found {}",
+            name,
+            expected,
+            values.len()
+        ),
+    )
+}
+
+/// `base` with every entry of `overrides` replacing or added to it.
+fn merge(base: &[(String, Node)], overrides: &[(String, Node)]) -> Vec<(String, Node)> {
+    let mut merged = base.to_vec();
+    for (key, value) in overrides {
+        match merged.iter_mut().find(|(existing, _)| existing == key) {
+            Some(entry) => entry.1 = value.clone(),
+            None => merged.push((key.clone(), value.clone())),
+        }
+    }
+    merged
+}
+
+fn check_keys(item: &Node, allowed: &[&str]) -> Result<(), SceneError> {
+    for (key, value) in item.as_mapping().unwrap_or_default() {
+        if !allowed.contains(&key.as_str()) {
+            return invalid(
+                value,
+                key,
+                format!("unexpected key, expected one of {}", allowed.join(", ")),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn required<'a>(item: &'a Node, key: &str) -> Result<&'a Node, SceneError> {
+    match item.get(key) {
+        Some(value) => Ok(value),
+        None => invalid(item, key, "missing"),
+    }
+}
+
+fn string<'a>(node: &'a Node, key: &str) -> Result<&'a str, SceneError> {
+    match node.as_str() {
+        Some(text) => Ok(text),
+        None => invalid(node, key, "expected a name"),
+    }
+}
+
+fn number(node: &Node, key: &str) -> Result<f64, SceneError> {
+    match node.as_str().map(str::parse::<f64>) {
+        Some(Ok(value)) if value.is_finite() => Ok(value),
+        Some(Ok(_)) => invalid(
+            node,
+            key,
+            format!(
+                "expected a finite number, found `{}`",
+                node.as_str().unwrap()
+            ),
+        ),
+        Some(Err(_)) => invalid(
+            node,
+            key,
+            format!("`{}` is not a number", node.as_str().unwrap()),
+        ),
+        None => invalid(node, key, "expected a number"),
+    }
+}
+
+fn size(node: &Node, key: &str) -> Result<usize, SceneError> {
+    match node.as_str().map(str::parse::<usize>) {
+        Some(Ok(value)) if value > 0 => Ok(value),
+        _ => invalid(node, key, "expected a positive whole number"),
+    }
+}
+
+fn boolean(node: &Node, key: &str) -> Result<bool, SceneError> {
+    match node.as_str() {
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        _ => invalid(node, key, "expected true or false"),
+    }
+}
+
+fn triple(node: &Node, key: &str) -> Result<[f64; 3], SceneError> {
+    match node.as_sequence() {
+        Some([x, y, z]) => Ok([number(x, key)?, number(y, key)?, number(z, key)?]),
+        _ => invalid(node, key, "expected a list of three numbers"),
+    }
+}
+
+fn color(node: &Node, key: &str) -> Result<Color, SceneError> {
+    let [r, g, b] = triple(node, key)?;
+    Ok(Color::new(r, g, b))
+}
+
+/// The `min`, `max` and `closed` keys of cylinders and cones.
+fn truncation(item: &Node) -> Result<(f64, f64, bool), SceneError> {
+    let minimum = match item.get("min") {
+        Some(node) => number(node, "min")?,
+        None => f64::NEG_INFINITY,
+    };
+    let maximum = match item.get("max") {
+        Some(node) => number(node, "max")?,
+        None => f64::INFINITY,
+    };
+    let closed = match item.get("closed") {
+        Some(node) => boolean(node, "closed")?,
+        None => false,
+    };
+    Ok((minimum, maximum, closed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+    use std::f64::consts::PI;
+
+    const CAMERA: &str = "\
+- add: camera
+  width: 20
+  height: 10
+  field-of-view: 1.0471975512
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+";
+
+    fn scene(rest: &str) -> Result<Scene, SceneError> {
+        Scene::parse(&format!("{}{}", CAMERA, rest))
+    }
+
+    fn error_of(rest: &str) -> (usize, String, String) {
+        match scene(rest) {
+            Err(SceneError::Invalid { line, key, message }) => (line, key, message),
+            other => panic!("expected an invalid scene, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn should_read_the_camera() {
+        let scene = scene("").unwrap();
+
+        assert_eq!(scene.camera.hsize(), 20);
+        assert_eq!(scene.camera.vsize(), 10);
+        assert!(crate::equal(scene.camera.field_of_view(), PI / 3.0));
+        assert_eq!(
+            *scene.camera.transform(),
+            Matrix::view_transform(
+                Point::new(0.0, 0.0, -5.0),
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0)
+            )
+        );
+    }
+
+    #[test]
+    fn should_read_lights() {
+        let scene = scene(
+            "\
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 0.5, 0.25]
+- add: light
+  at: [0, 1, 0]
+  intensity: [1, 1, 1]
+  shadow: false
+",
+        )
+        .unwrap();
+
+        assert_eq!(scene.world.lights.len(), 2);
+        assert_eq!(
+            scene.world.lights[0].position,
+            Point::new(-10.0, 10.0, -10.0)
+        );
+        assert_eq!(scene.world.lights[0].intensity, Color::new(1.0, 0.5, 0.25));
+        assert!(scene.world.lights[0].casts_shadows);
+        assert!(!scene.world.lights[1].casts_shadows);
+    }
+
+    #[test]
+    fn should_read_shapes_with_materials_and_transforms() {
+        let scene = scene(
+            "\
+- add: sphere
+  material:
+    color: [1, 0, 0]
+    diffuse: 0.7
+    refractive-index: 1.5
+  transform:
+    - [scale, 2, 2, 2]
+    - [translate, 1, 0, 0]
+  shadow: false
+- add: cylinder
+  min: 0
+  max: 2
+  closed: true
+",
+        )
+        .unwrap();
+
+        let sphere = scene.world.objects[0].as_ref();
+        assert_eq!(sphere.material().color, Color::new(1.0, 0.0, 0.0));
+        assert_eq!(sphere.material().diffuse, 0.7);
+        assert_eq!(sphere.material().refractive_index, 1.5);
+        assert_eq!(
+            *sphere.transform(),
+            Matrix::translation(1.0, 0.0, 0.0) * Matrix::scaling(2.0, 2.0, 2.0)
+        );
+        assert!(!sphere.casts_shadow());
+        let bounds = scene.world.objects[1].bounds();
+        assert_eq!((bounds.min.y, bounds.max.y), (0.0, 2.0));
+    }
+
+    #[test]
+    fn should_expand_definitions() {
+        let scene = scene(
+            "\
+- define: white-material
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+- define: blue-material
+  extend: white-material
+  value:
+    color: [0.5, 0.5, 1]
+- define: standard-transform
+  value:
+    - [translate, 1, -1, 1]
+    - [scale, 0.5, 0.5, 0.5]
+- add: cube
+  material: blue-material
+  transform:
+    - standard-transform
+    - [scale, 3, 3, 3]
+- add: plane
+  material:
+    extend: white-material
+    ambient: 0.5
+",
+        )
+        .unwrap();
+
+        let cube = scene.world.objects[0].as_ref();
+        assert_eq!(cube.material().color, Color::new(0.5, 0.5, 1.0));
+        assert_eq!(cube.material().diffuse, 0.7);
+        assert_eq!(
+            *cube.transform(),
+            Matrix::scaling(3.0, 3.0, 3.0)
+                * Matrix::scaling(0.5, 0.5, 0.5)
+                * Matrix::translation(1.0, -1.0, 1.0)
+        );
+        let plane = scene.world.objects[1].as_ref();
+        assert_eq!(plane.material().diffuse, 0.7);
+        assert_eq!(plane.material().ambient, 0.5);
+    }
+
+    #[test]
+    fn should_read_groups_csg_and_patterns() {
+        let scene = scene(
+            "\
+- add: group
+  transform: [[translate, 0, 1, 0]]
+  children:
+    - add: sphere
+    - add: csg
+      operation: difference
+      left: { add: cube }
+      right:
+        add: sphere
+        transform: [[scale, 0.5, 0.5, 0.5]]
+- add: plane
+  material:
+    pattern:
+      type: checkers
+      colors: [[1, 1, 1], [0, 0, 0]]
+      transform: [[scale, 0.5, 0.5, 0.5]]
+",
+        )
+        .unwrap();
+
+        let group = scene.world.objects[0].as_ref();
+        assert_eq!(group.parent_space_bounds().max, Point::new(1.0, 2.0, 1.0));
+        let plane = scene.world.objects[1].as_ref();
+        let pattern = plane.material().pattern.as_ref().unwrap();
+        assert_eq!(*pattern.transform(), Matrix::scaling(0.5, 0.5, 0.5));
+        assert_eq!(
+            pattern.pattern_at(Point::new(0.75, 0.0, 0.0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_render_a_parsed_scene() {
+        let scene = scene(
+            "\
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+- add: sphere
+  material: { color: [0.8, 1.0, 0.6], diffuse: 0.7, specular: 0.2 }
+",
+        )
+        .unwrap();
+
+        let image = scene.camera.render(&scene.world);
+
+        assert!(!image.get_pixel(10, 5).is_black());
+        assert!(image.get_pixel(0, 0).is_black());
+    }
+
+    #[test]
+    fn should_point_at_the_offending_line_and_key() {
+        let cases = [
+            (
+                "- add: sphere\n  material:\n    color: [1, 0]\n",
+                10,
+                "color",
+            ),
+            (
+                "- add: sphere\n  transform:\n    - [rotate-x, 1, 2]\n",
+                10,
+                "transform",
+            ),
+            (
+                "- add: sphere\n  transform:\n    - [spin, 1]\n",
+                10,
+                "transform",
+            ),
+            ("- add: sphere\n  material: chrome\n", 9, "material"),
+            ("- add: teapot\n", 8, "add"),
+            ("- add: sphere\n  radius: 2\n", 9, "radius"),
+            ("- add: light\n  intensity: [1, 1, 1]\n", 8, "at"),
+            ("- add: cone\n  closed: yes\n", 9, "closed"),
+            (
+                "- add: sphere\n  material:\n    shininess: lots\n",
+                10,
+                "shininess",
+            ),
+            (
+                "- define: loop\n  value: [loop]\n- add: sphere\n  transform: loop\n",
+                9,
+                "transform",
+            ),
+        ];
+
+        for (rest, line, key) in cases {
+            let (actual_line, actual_key, message) = error_of(rest);
+            assert_eq!(
+                (actual_line, actual_key.as_str()),
+                (line, key),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_numbers_that_are_not_finite() {
+        let cases = [
+            (
+                "- add: light\n  at: [nan, 0, 0]\n  intensity: [1, 1, 1]\n",
+                "at",
+            ),
+            ("- add: sphere\n  material:\n    diffuse: NaN\n", "diffuse"),
+            (
+                "- add: sphere\n  transform:\n    - [translate, inf, 0, 0]\n",
+                "transform",
+            ),
+            (
+                "- add: cube\n  transform:\n    - [scale, 1, -infinity, 1]\n",
+                "transform",
+            ),
+        ];
+
+        for (rest, key) in cases {
+            let (_, actual_key, message) = error_of(rest);
+            assert_eq!(actual_key, key);
+            assert!(
+                message.starts_with("expected a finite number"),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_materials_that_extend_themselves() {
+        let cases = [
+            "- define: m\n  value: {extend: m}\n- add: sphere\n  material: m\n",
+            "\
+- define: a
+  value: {extend: b}
+- define: b
+  value: {extend: a}
+- add: sphere
+  material: {extend: a, ambient: 0.5}
+",
+        ];
+
+        for rest in cases {
+            let (_, key, message) = error_of(rest);
+            assert_eq!(key, "extend", "{}", message);
+        }
+    }
+
+    #[test]
+    fn should_describe_errors() {
+        let error = scene("- add: sphere\n  transform:\n    - [rotate-x, 1, 2]\n").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "line 10, key `transform`: `rotate-x` takes 1 values, found 2"
+        );
+    }
+
+    #[test]
+    fn should_name_the_object_whose_transform_is_not_invertible() {
+        let shape = error_of("- add: cube\n  transform:\n    - [scale, 1, 0, 1]\n");
+        let pattern = error_of(
+            "\
+- add: plane
+  material:
+    pattern:
+      type: stripes
+      colors: [[1, 1, 1], [0, 0, 0]]
+      transform:
+        - [shear, 1, 0, 1, 0, 0, 0]
+",
+        );
+
+        assert_eq!(
+            shape,
+            (
+                10,
+                "transform".to_string(),
+                "`cube`: transform is not invertible, look for a zero scale or a degenerate shear"
+                    .to_string()
+            )
+        );
+        assert_eq!((pattern.0, pattern.1.as_str()), (14, "transform"));
+        assert!(pattern.2.starts_with("the `stripes` pattern: "));
+    }
+
+    #[test]
+    fn should_reject_a_camera_that_cannot_see() {
+        let error = Scene::parse(&CAMERA.replace("to: [0, 0, 0]", "to: [0, 5, -5]")).unwrap_err();
+
+        assert!(matches!(error, SceneError::Invalid { line: 1, ref key, .. } if key == "to"));
+    }
+
+    #[test]
+    fn should_require_a_camera() {
+        let error = Scene::parse("- add: sphere\n").unwrap_err();
+
+        assert!(matches!(error, SceneError::Invalid { ref key, .. } if key == "add"));
+    }
+
+    #[test]
+    fn should_pass_on_yaml_errors() {
+        let error = scene("- add: sphere\n   bad: indent\n").unwrap_err();
+
+        assert!(matches!(error, SceneError::Yaml(YamlError { line: 9, .. })));
+    }
+}
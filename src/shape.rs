@@ -0,0 +1,326 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::material::Material;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::stats;
+use crate::transform::{InvertibleTransform, SingularTransformError};
+use crate::vector::Vector;
+use std::fmt;
+use std::sync::Arc;
+
+/// State shared by every shape, so implementors only have to store one field.
+#[derive(Debug, Clone)]
+pub struct ShapeProperties {
+    pub transform: InvertibleTransform,
+    pub material: Material,
+    pub casts_shadow: bool,
+    pub parent: Option<Arc<Parent>>,
+}
+
+impl ShapeProperties {
+    pub fn new() -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            material: Material::new(),
+            casts_shadow: true,
+            parent: None,
+        }
+    }
+}
+
+impl Default for ShapeProperties {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A snapshot of the transform of the group a shape belongs to, linked to
+/// that group's own parent.
+#[derive(Debug, Clone)]
+pub struct Parent {
+    pub transform: InvertibleTransform,
+    pub parent: Option<Arc<Parent>>,
+}
+
+impl Parent {
+    pub fn world_to_object(&self, point: Point) -> Point {
+        let point = match &self.parent {
+            Some(parent) => parent.world_to_object(point),
+            None => point,
+        };
+        *self.transform.inverse() * point
+    }
+
+    pub fn normal_to_world(&self, normal: Vector) -> Vector {
+        let normal = (*self.transform.inverse_transpose() * normal).normalize();
+        match &self.parent {
+            Some(parent) => parent.normal_to_world(normal),
+            None => normal,
+        }
+    }
+}
+
+/// A primitive that can be placed in a scene.
+///
+/// Implementors only deal with object space in `local_intersect` and
+/// `local_normal_at`; converting rays and normals between world and object
+/// space is done once here.
+pub trait Shape: fmt::Debug + Send + Sync {
+    fn properties(&self) -> &ShapeProperties;
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties;
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_>;
+
+    /// `hit` carries extra data such as u/v for shapes that interpolate normals.
+    fn local_normal_at(&self, point: Point, hit: &Intersection) -> Vector;
+
+    /// The box around this shape in object space.
+    fn bounds(&self) -> BoundingBox;
+
+    /// The box around this shape in the space of its parent.
+    fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.transform())
+    }
+
+    /// Organizes composite shapes into a bounding volume hierarchy with at
+    /// most `threshold` children per node. Primitives have nothing to divide.
+    fn divide(&mut self, _threshold: usize) {}
+
+    fn transform(&self) -> &Matrix<4> {
+        self.properties().transform.matrix()
+    }
+
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.properties().transform
+    }
+
+    /// Sets the transform, inverting it once. Panics if it isn't invertible.
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.set_invertible_transform(InvertibleTransform::new(transform));
+    }
+
+    /// Like `set_transform`, but leaves the shape as it was when `transform`
+    /// isn't invertible.
+    fn try_set_transform(&mut self, transform: Matrix<4>) -> Result<(), SingularTransformError> {
+        self.set_invertible_transform(InvertibleTransform::try_new(transform)?);
+        Ok(())
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.properties_mut().transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.properties().material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.properties_mut().material = material;
+    }
+
+    /// Whether this shape blocks light, e.g. false for glass panes or light fixtures.
+    fn casts_shadow(&self) -> bool {
+        self.properties().casts_shadow
+    }
+
+    fn set_casts_shadow(&mut self, casts_shadow: bool) {
+        self.properties_mut().casts_shadow = casts_shadow;
+    }
+
+    fn parent(&self) -> Option<&Arc<Parent>> {
+        self.properties().parent.as_ref()
+    }
+
+    /// Called by groups when this shape is added to them.
+    fn set_parent(&mut self, parent: Option<Arc<Parent>>) {
+        self.properties_mut().parent = parent;
+    }
+
+    /// Whether `other` is this shape or, for composite shapes, one of its descendants.
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        stats::record(|stats| stats.primitive_tests += 1);
+        let local_ray = ray.transform(self.invertible_transform().inverse());
+        self.local_intersect(&local_ray)
+    }
+
+    /// Converts a world space point to object space, going through every enclosing group.
+    fn world_to_object(&self, point: Point) -> Point {
+        let point = match self.parent() {
+            Some(parent) => parent.world_to_object(point),
+            None => point,
+        };
+        *self.invertible_transform().inverse() * point
+    }
+
+    /// Converts an object space normal to world space, going through every enclosing group.
+    fn normal_to_world(&self, normal: Vector) -> Vector {
+        let normal = (*self.invertible_transform().inverse_transpose() * normal).normalize();
+        match self.parent() {
+            Some(parent) => parent.normal_to_world(normal),
+            None => normal,
+        }
+    }
+
+    fn normal_at(&self, world_point: Point, hit: &Intersection) -> Vector {
+        let local_point = self.world_to_object(world_point);
+        let local_normal = self.local_normal_at(local_point, hit);
+        self.normal_to_world(local_normal)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+    use std::sync::Mutex;
+
+    /// A shape that records the object space ray it was asked to intersect.
+    #[derive(Debug, Default)]
+    pub struct TestShape {
+        properties: ShapeProperties,
+        pub saved_ray: Mutex<Option<Ray>>,
+    }
+
+    impl Shape for TestShape {
+        fn properties(&self) -> &ShapeProperties {
+            &self.properties
+        }
+
+        fn properties_mut(&mut self) -> &mut ShapeProperties {
+            &mut self.properties
+        }
+
+        fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+            *self.saved_ray.lock().unwrap() = Some(*ray);
+            Intersections::default()
+        }
+
+        fn local_normal_at(&self, point: Point, _hit: &Intersection) -> Vector {
+            Vector::new(point.x, point.y, point.z)
+        }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn should_have_identity_as_default_transformation() {
+        let shape = TestShape::default();
+        assert_eq!(*shape.transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_assign_a_transformation() {
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::translation(2.0, 3.0, 4.0));
+        assert_eq!(*shape.transform(), Matrix::translation(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn should_keep_its_transformation_when_given_a_singular_one() {
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::translation(2.0, 3.0, 4.0));
+
+        let result = shape.try_set_transform(Matrix::scaling(1.0, 0.0, 1.0));
+
+        assert!(result.is_err());
+        assert_eq!(*shape.transform(), Matrix::translation(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn should_have_a_default_material() {
+        let shape = TestShape::default();
+        assert_eq!(*shape.material(), Material::new());
+    }
+
+    #[test]
+    fn should_assign_a_material() {
+        let mut shape = TestShape::default();
+        let mut material = Material::new();
+        material.ambient = 1.0;
+
+        shape.set_material(material.clone());
+
+        assert_eq!(*shape.material(), material);
+    }
+
+    #[test]
+    fn should_cast_shadows_by_default() {
+        let mut shape = TestShape::default();
+        assert!(shape.casts_shadow());
+
+        shape.set_casts_shadow(false);
+        assert!(!shape.casts_shadow());
+    }
+
+    #[test]
+    fn should_query_the_bounds_of_a_shape_in_its_parent_space() {
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::translation(1.0, -3.0, 5.0) * Matrix::scaling(0.5, 2.0, 4.0));
+
+        let bounds = shape.parent_space_bounds();
+
+        assert_eq!(bounds.min, Point::new(0.5, -5.0, 1.0));
+        assert_eq!(bounds.max, Point::new(1.5, -1.0, 9.0));
+    }
+
+    #[test]
+    fn should_intersect_a_scaled_shape_with_a_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+
+        shape.intersect(&ray);
+
+        let saved_ray = shape.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved_ray.origin, Point::new(0.0, 0.0, -2.5));
+        assert_eq!(saved_ray.direction, Vector::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn should_intersect_a_translated_shape_with_a_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+
+        shape.intersect(&ray);
+
+        let saved_ray = shape.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved_ray.origin, Point::new(-5.0, 0.0, -5.0));
+        assert_eq!(saved_ray.direction, Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_translated_shape() {
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::translation(0.0, 1.0, 0.0));
+
+        let n = shape.normal_at(
+            Point::new(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+            &Intersection::new(0.0, &shape),
+        );
+
+        assert_eq!(n, Vector::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_transformed_shape() {
+        let mut shape = TestShape::default();
+        shape.set_transform(Matrix::scaling(1.0, 0.5, 1.0) * Matrix::rotation_z(PI / 5.0));
+
+        let n = shape.normal_at(
+            Point::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0)),
+            &Intersection::new(0.0, &shape),
+        );
+
+        assert_eq!(n, Vector::new(0.0, 0.97014, -0.24254));
+    }
+}
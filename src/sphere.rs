@@ -0,0 +1,230 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+
+#[derive(Debug, Clone, Default)]
+pub struct Sphere {
+    properties: ShapeProperties,
+}
+
+impl Sphere {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Shape for Sphere {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let sphere_to_ray = ray.origin - Point::new(0.0, 0.0, 0.0);
+
+        let a = ray.direction.dot(&ray.direction);
+        let b = 2.0 * ray.direction.dot(&sphere_to_ray);
+        let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
+
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return Intersections::default();
+        }
+
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+        Intersections::new(vec![
+            Intersection::new(t1, self),
+            Intersection::new(t2, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Point, _hit: &Intersection) -> Vector {
+        point - Point::new(0.0, 0.0, 0.0)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::matrix::Matrix;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_intersect_a_sphere_at_two_points() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[1].t, 6.0);
+    }
+
+    #[test]
+    fn should_intersect_a_sphere_at_a_tangent() {
+        let ray = Ray::new(Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 5.0);
+        assert_eq!(xs[1].t, 5.0);
+    }
+
+    #[test]
+    fn should_miss_a_sphere() {
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_intersect_when_ray_originates_inside_a_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, -1.0);
+        assert_eq!(xs[1].t, 1.0);
+    }
+
+    #[test]
+    fn should_intersect_a_sphere_behind_a_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, -6.0);
+        assert_eq!(xs[1].t, -4.0);
+    }
+
+    #[test]
+    fn should_set_the_object_on_the_intersection() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new();
+
+        let xs = sphere.intersect(&ray);
+
+        assert!(std::ptr::addr_eq(xs[0].object, &sphere));
+        assert!(std::ptr::addr_eq(xs[1].object, &sphere));
+    }
+
+    #[test]
+    fn should_have_identity_as_default_transformation() {
+        let sphere = Sphere::new();
+        assert_eq!(*sphere.transform(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_change_the_transformation_of_a_sphere() {
+        let mut sphere = Sphere::new();
+        let t = Matrix::translation(2.0, 3.0, 4.0);
+
+        sphere.set_transform(t);
+
+        assert_eq!(*sphere.transform(), t);
+    }
+
+    #[test]
+    fn should_intersect_a_scaled_sphere_with_a_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::scaling(2.0, 2.0, 2.0));
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 7.0);
+    }
+
+    #[test]
+    fn should_intersect_a_translated_sphere_with_a_ray() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::translation(5.0, 0.0, 0.0));
+
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_sphere_at_a_point_on_an_axis() {
+        let sphere = Sphere::new();
+
+        assert_eq!(
+            sphere.normal_at(Point::new(1.0, 0.0, 0.0), &Intersection::new(0.0, &sphere)),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            sphere.normal_at(Point::new(0.0, 1.0, 0.0), &Intersection::new(0.0, &sphere)),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            sphere.normal_at(Point::new(0.0, 0.0, 1.0), &Intersection::new(0.0, &sphere)),
+            Vector::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_sphere_at_a_nonaxial_point() {
+        let sphere = Sphere::new();
+        let a = 3.0_f64.sqrt() / 3.0;
+
+        let n = sphere.normal_at(Point::new(a, a, a), &Intersection::new(0.0, &sphere));
+
+        assert_eq!(n, Vector::new(a, a, a));
+        assert_eq!(n, n.normalize());
+    }
+
+    #[test]
+    fn should_compute_the_normal_on_a_transformed_sphere() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix::scaling(1.0, 0.5, 1.0) * Matrix::rotation_z(PI / 5.0));
+
+        let n = sphere.normal_at(
+            Point::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0)),
+            &Intersection::new(0.0, &sphere),
+        );
+
+        assert_eq!(n, Vector::new(0.0, 0.97014, -0.24254));
+    }
+
+    #[test]
+    fn should_assign_a_material_to_a_sphere() {
+        let mut sphere = Sphere::new();
+        let mut material = Material::new();
+        material.ambient = 1.0;
+
+        sphere.set_material(material.clone());
+
+        assert_eq!(*sphere.material(), material);
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let bounds = Sphere::new().bounds();
+
+        assert_eq!(bounds.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Point::new(1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,76 @@
+use std::cell::Cell;
+use std::ops;
+
+/// How much work intersecting rays with the scene took.
+///
+/// Counters are kept per thread, so concurrent renders never mix their numbers.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct IntersectionStats {
+    /// Bounding boxes tested against a ray.
+    pub bounds_tests: u64,
+    /// Bounding volume hierarchy nodes whose box was hit and whose children were visited.
+    pub nodes_visited: u64,
+    /// Primitive shapes tested against a ray.
+    pub primitive_tests: u64,
+}
+
+impl ops::Add for IntersectionStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            bounds_tests: self.bounds_tests + rhs.bounds_tests,
+            nodes_visited: self.nodes_visited + rhs.nodes_visited,
+            primitive_tests: self.primitive_tests + rhs.primitive_tests,
+        }
+    }
+}
+
+thread_local! {
+    static STATS: Cell<IntersectionStats> = Cell::new(IntersectionStats::default());
+}
+
+pub fn record(update: impl FnOnce(&mut IntersectionStats)) {
+    STATS.with(|stats| {
+        let mut current = stats.get();
+        update(&mut current);
+        stats.set(current);
+    });
+}
+
+/// Returns the counters of the current thread and resets them.
+pub fn take() -> IntersectionStats {
+    STATS.with(|stats| stats.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_and_reset() {
+        take();
+
+        record(|stats| stats.bounds_tests += 2);
+        record(|stats| stats.primitive_tests += 1);
+
+        let expected = IntersectionStats {
+            bounds_tests: 2,
+            nodes_visited: 0,
+            primitive_tests: 1,
+        };
+        assert_eq!(take(), expected);
+        assert_eq!(take(), IntersectionStats::default());
+    }
+
+    #[test]
+    fn should_keep_counters_per_thread() {
+        take();
+        record(|stats| stats.nodes_visited += 1);
+
+        let other = std::thread::spawn(take).join().unwrap();
+
+        assert_eq!(other, IntersectionStats::default());
+        assert_eq!(take().nodes_visited, 1);
+    }
+}
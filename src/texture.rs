@@ -0,0 +1,524 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::pattern::Pattern;
+use crate::point::Point;
+use crate::ppm::PpmError;
+use crate::transform::InvertibleTransform;
+use std::f64::consts::PI;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// A 2D pattern over texture coordinates, `u` and `v` both running from 0 to 1.
+pub trait UvPattern: fmt::Debug + Send + Sync {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+}
+
+/// A checkerboard of `width` by `height` squares.
+#[derive(Debug, Clone)]
+pub struct UvCheckers {
+    pub width: f64,
+    pub height: f64,
+    pub a: Color,
+    pub b: Color,
+}
+
+impl UvCheckers {
+    pub fn new(width: f64, height: f64, a: Color, b: Color) -> Self {
+        Self {
+            width,
+            height,
+            a,
+            b,
+        }
+    }
+}
+
+impl UvPattern for UvCheckers {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let sum = (u * self.width).floor() + (v * self.height).floor();
+        if sum as i64 % 2 == 0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// Samples an image, `v` = 1 being its top row, blending the four nearest pixels.
+#[derive(Debug, Clone)]
+pub struct UvImage {
+    canvas: Canvas,
+}
+
+impl UvImage {
+    pub fn new(canvas: Canvas) -> Self {
+        Self { canvas }
+    }
+
+    pub fn from_ppm<R: Read>(reader: R) -> Result<Self, PpmError> {
+        Canvas::from_ppm(reader).map(Self::new)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PpmError> {
+        Self::from_ppm(BufReader::new(File::open(path)?))
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+}
+
+impl UvPattern for UvImage {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let (width, height) = (self.canvas.width(), self.canvas.height);
+        if width == 0 || height == 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let x = u.clamp(0.0, 1.0) * (width - 1) as f64;
+        let y = (1.0 - v.clamp(0.0, 1.0)) * (height - 1) as f64;
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+        let top = lerp(
+            self.canvas.get_pixel(x0, y0),
+            self.canvas.get_pixel(x1, y0),
+            tx,
+        );
+        let bottom = lerp(
+            self.canvas.get_pixel(x0, y1),
+            self.canvas.get_pixel(x1, y1),
+            tx,
+        );
+        lerp(top, bottom, ty)
+    }
+}
+
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    a + (b - a) * t
+}
+
+/// How a point on a surface is flattened into `u` and `v`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+}
+
+impl UvMapping {
+    pub fn map(self, point: Point) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+        }
+    }
+}
+
+/// Maps a point on a unit sphere like longitude and latitude.
+pub fn spherical_map(point: Point) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let radius = (point.x.powi(2) + point.y.powi(2) + point.z.powi(2)).sqrt();
+    let phi = (point.y / radius).acos();
+
+    // raw_u runs from -0.5 to 0.5; flip it so u grows counterclockwise seen from above.
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+/// Tiles the xz plane with the texture every unit.
+pub fn planar_map(point: Point) -> (f64, f64) {
+    (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0))
+}
+
+/// Wraps the texture around the y axis, repeating every unit of height.
+pub fn cylindrical_map(point: Point) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), point.y.rem_euclid(1.0))
+}
+
+/// A pattern that looks up its color in a UV pattern after mapping the point to `u` and `v`.
+#[derive(Debug)]
+pub struct TextureMapPattern {
+    transform: InvertibleTransform,
+    pub uv_pattern: Box<dyn UvPattern>,
+    pub mapping: UvMapping,
+}
+
+impl TextureMapPattern {
+    pub fn new(uv_pattern: Box<dyn UvPattern>, mapping: UvMapping) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            uv_pattern,
+            mapping,
+        }
+    }
+}
+
+impl Pattern for TextureMapPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let (u, v) = self.mapping.map(point);
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CubeFace {
+    Left,
+    Front,
+    Right,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    /// The face of the cube from -1 to 1 that `point` lies on, going by its largest coordinate.
+    pub fn from_point(point: Point) -> Self {
+        let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+        if coord == point.x {
+            CubeFace::Right
+        } else if coord == -point.x {
+            CubeFace::Left
+        } else if coord == point.y {
+            CubeFace::Up
+        } else if coord == -point.y {
+            CubeFace::Down
+        } else if coord == point.z {
+            CubeFace::Front
+        } else {
+            CubeFace::Back
+        }
+    }
+
+    /// Maps `point` onto this face, as if looking at it from outside the cube.
+    pub fn uv(self, point: Point) -> (f64, f64) {
+        let unit = |value: f64| value.rem_euclid(2.0) / 2.0;
+        match self {
+            CubeFace::Front => (unit(point.x + 1.0), unit(point.y + 1.0)),
+            CubeFace::Back => (unit(1.0 - point.x), unit(point.y + 1.0)),
+            CubeFace::Left => (unit(point.z + 1.0), unit(point.y + 1.0)),
+            CubeFace::Right => (unit(1.0 - point.z), unit(point.y + 1.0)),
+            CubeFace::Up => (unit(point.x + 1.0), unit(1.0 - point.z)),
+            CubeFace::Down => (unit(point.x + 1.0), unit(point.z + 1.0)),
+        }
+    }
+}
+
+/// A different UV pattern on each of the six faces of a cube.
+#[derive(Debug)]
+pub struct CubeMapPattern {
+    transform: InvertibleTransform,
+    /// In the order of `CubeFace`: left, front, right, back, up, down.
+    pub faces: [Box<dyn UvPattern>; 6],
+}
+
+impl CubeMapPattern {
+    pub fn new(faces: [Box<dyn UvPattern>; 6]) -> Self {
+        Self {
+            transform: InvertibleTransform::identity(),
+            faces,
+        }
+    }
+}
+
+impl Pattern for CubeMapPattern {
+    fn invertible_transform(&self) -> &InvertibleTransform {
+        &self.transform
+    }
+
+    fn set_invertible_transform(&mut self, transform: InvertibleTransform) {
+        self.transform = transform;
+    }
+
+    fn local_pattern_at(&self, point: Point) -> Color {
+        let face = CubeFace::from_point(point);
+        let (u, v) = face.uv(point);
+        self.faces[face as usize].uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    fn black() -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            crate::equal(actual.0, expected.0) && crate::equal(actual.1, expected.1),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    /// A face that only shows its main color, with four colored corners.
+    #[derive(Debug)]
+    struct AlignCheck {
+        main: Color,
+        ul: Color,
+        ur: Color,
+        bl: Color,
+        br: Color,
+    }
+
+    impl UvPattern for AlignCheck {
+        fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+            match (u < 0.2, u > 0.8, v < 0.2, v > 0.8) {
+                (true, _, _, true) => self.ul,
+                (_, true, _, true) => self.ur,
+                (true, _, true, _) => self.bl,
+                (_, true, true, _) => self.br,
+                _ => self.main,
+            }
+        }
+    }
+
+    #[test]
+    fn should_checker_in_uv_space() {
+        let checkers = UvCheckers::new(2.0, 2.0, black(), white());
+
+        assert_eq!(checkers.uv_pattern_at(0.0, 0.0), black());
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.0), white());
+        assert_eq!(checkers.uv_pattern_at(0.0, 0.5), white());
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.5), black());
+        assert_eq!(checkers.uv_pattern_at(1.0, 1.0), black());
+    }
+
+    #[test]
+    fn should_map_a_sphere() {
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Point::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (Point::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), (0.25, 0.75)),
+        ];
+
+        for (point, uv) in cases {
+            assert_uv(spherical_map(point), uv);
+        }
+    }
+
+    #[test]
+    fn should_map_a_plane() {
+        let cases = [
+            (Point::new(0.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -0.25), (0.25, 0.75)),
+            (Point::new(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Point::new(1.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point::new(0.25, 0.0, -1.75), (0.25, 0.25)),
+            (Point::new(1.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), (0.0, 0.0)),
+        ];
+
+        for (point, uv) in cases {
+            assert_uv(planar_map(point), uv);
+        }
+    }
+
+    #[test]
+    fn should_map_a_cylinder() {
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point::new(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (Point::new(0.0, 1.0, -1.0), (0.0, 0.0)),
+            (Point::new(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+            (Point::new(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Point::new(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+            (Point::new(0.0, -0.25, 1.0), (0.5, 0.75)),
+            (Point::new(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.625, 0.5)),
+            (Point::new(-1.0, 1.25, 0.0), (0.75, 0.25)),
+            (
+                Point::new(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2),
+                (0.875, 0.5),
+            ),
+        ];
+
+        for (point, uv) in cases {
+            assert_uv(cylindrical_map(point), uv);
+        }
+    }
+
+    #[test]
+    fn should_apply_a_texture_map_to_a_point() {
+        let checkers = UvCheckers::new(16.0, 8.0, black(), white());
+        let pattern = TextureMapPattern::new(Box::new(checkers), UvMapping::Spherical);
+
+        let cases = [
+            (Point::new(0.4315, 0.4670, 0.7719), white()),
+            (Point::new(-0.9654, 0.2552, -0.0534), black()),
+            (Point::new(0.1039, 0.7090, 0.6975), white()),
+            (Point::new(-0.4986, -0.7856, -0.3663), black()),
+            (Point::new(-0.0317, -0.9395, 0.3411), black()),
+            (Point::new(0.4809, -0.7721, 0.4154), black()),
+            (Point::new(0.0285, -0.9612, -0.2745), black()),
+            (Point::new(-0.5734, -0.2162, -0.7903), white()),
+            (Point::new(0.7688, -0.1470, 0.6223), black()),
+            (Point::new(-0.7652, 0.2175, 0.6060), black()),
+        ];
+
+        for (point, color) in cases {
+            assert_eq!(pattern.pattern_at(point), color, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn should_identify_the_face_of_a_cube() {
+        let cases = [
+            (Point::new(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Point::new(1.1, -0.75, 0.8), CubeFace::Right),
+            (Point::new(0.1, 0.6, 0.9), CubeFace::Front),
+            (Point::new(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Point::new(0.5, 1.0, 0.9), CubeFace::Up),
+            (Point::new(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+
+        for (point, face) in cases {
+            assert_eq!(CubeFace::from_point(point), face);
+        }
+    }
+
+    #[test]
+    fn should_map_each_face_of_a_cube() {
+        let cases = [
+            (
+                CubeFace::Front,
+                Point::new(-0.5, 0.5, 1.0),
+                Point::new(0.5, -0.5, 1.0),
+            ),
+            (
+                CubeFace::Back,
+                Point::new(0.5, 0.5, -1.0),
+                Point::new(-0.5, -0.5, -1.0),
+            ),
+            (
+                CubeFace::Left,
+                Point::new(-1.0, 0.5, -0.5),
+                Point::new(-1.0, -0.5, 0.5),
+            ),
+            (
+                CubeFace::Right,
+                Point::new(1.0, 0.5, 0.5),
+                Point::new(1.0, -0.5, -0.5),
+            ),
+            (
+                CubeFace::Up,
+                Point::new(-0.5, 1.0, -0.5),
+                Point::new(0.5, 1.0, 0.5),
+            ),
+            (
+                CubeFace::Down,
+                Point::new(-0.5, -1.0, 0.5),
+                Point::new(0.5, -1.0, -0.5),
+            ),
+        ];
+
+        for (face, upper_left, lower_right) in cases {
+            assert_uv(face.uv(upper_left), (0.25, 0.75));
+            assert_uv(face.uv(lower_right), (0.75, 0.25));
+        }
+    }
+
+    #[test]
+    fn should_find_colors_on_a_mapped_cube() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let yellow = Color::new(1.0, 1.0, 0.0);
+        let brown = Color::new(1.0, 0.5, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+        let cyan = Color::new(0.0, 1.0, 1.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        let purple = Color::new(1.0, 0.0, 1.0);
+        let face = |main, ul, ur, bl, br| -> Box<dyn UvPattern> {
+            Box::new(AlignCheck {
+                main,
+                ul,
+                ur,
+                bl,
+                br,
+            })
+        };
+        let pattern = CubeMapPattern::new([
+            face(yellow, cyan, red, blue, brown),
+            face(cyan, red, yellow, brown, green),
+            face(red, yellow, purple, green, white()),
+            face(green, purple, cyan, white(), blue),
+            face(brown, cyan, purple, red, yellow),
+            face(purple, brown, green, blue, white()),
+        ]);
+
+        let cases = [
+            (Point::new(-1.0, 0.0, 0.0), yellow),
+            (Point::new(-1.0, 0.9, -0.9), cyan),
+            (Point::new(-1.0, 0.9, 0.9), red),
+            (Point::new(-1.0, -0.9, -0.9), blue),
+            (Point::new(-1.0, -0.9, 0.9), brown),
+            (Point::new(0.0, 0.0, 1.0), cyan),
+            (Point::new(-0.9, 0.9, 1.0), red),
+            (Point::new(0.9, -0.9, 1.0), green),
+            (Point::new(1.0, 0.0, 0.0), red),
+            (Point::new(1.0, 0.9, 0.9), yellow),
+            (Point::new(0.0, 0.0, -1.0), green),
+            (Point::new(0.9, 0.9, -1.0), purple),
+            (Point::new(0.0, 1.0, 0.0), brown),
+            (Point::new(-0.9, 1.0, -0.9), cyan),
+            (Point::new(0.0, -1.0, 0.0), purple),
+            (Point::new(-0.9, -1.0, 0.9), brown),
+        ];
+
+        for (point, color) in cases {
+            assert_eq!(pattern.pattern_at(point), color, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn should_sample_image_pixels_exactly_at_their_positions() {
+        let ppm = b"P3\n3 2\n255\n255 0 0  0 255 0  0 0 255\n0 0 0  255 255 255  51 51 51\n";
+        let image = UvImage::from_ppm(&ppm[..]).unwrap();
+
+        assert_eq!(image.uv_pattern_at(0.0, 1.0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(image.uv_pattern_at(1.0, 1.0), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(image.uv_pattern_at(0.5, 0.0), white());
+        assert_eq!(image.uv_pattern_at(1.0, 0.0), Color::new(0.2, 0.2, 0.2));
+    }
+
+    #[test]
+    fn should_blend_image_pixels_bilinearly() {
+        let ppm = b"P2\n2 2\n100\n0 100\n100 20\n";
+        let image = UvImage::from_ppm(&ppm[..]).unwrap();
+
+        assert_eq!(image.uv_pattern_at(0.5, 1.0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(image.uv_pattern_at(0.5, 0.5), Color::new(0.55, 0.55, 0.55));
+        assert_eq!(image.uv_pattern_at(0.25, 0.0), Color::new(0.8, 0.8, 0.8));
+        assert_eq!(image.uv_pattern_at(2.0, -1.0), Color::new(0.2, 0.2, 0.2));
+    }
+
+    #[test]
+    fn should_fail_to_load_a_broken_image() {
+        assert!(UvImage::from_ppm(&b"P3\n2 2\n255\n"[..]).is_err());
+    }
+}
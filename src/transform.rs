@@ -0,0 +1,349 @@
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::vector::Vector;
+use std::fmt;
+
+/// Builds a `Matrix<4>` from operations listed in the order they are applied.
+///
+/// `Transform::identity().rotate_x(a).scale(5.0, 5.0, 5.0).translate(10.0, 5.0, 7.0)`
+/// is the same matrix as `translation * scaling * rotation_x`. The inverse is
+/// kept up to date from the inverse of each operation, which is known in
+/// closed form, so it never needs the general `Matrix::inverse`.
+///
+/// Scaling by zero or a degenerate shear can't be undone and leaves a
+/// non-finite inverse, which converting to an `InvertibleTransform` rejects.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix<4>,
+    inverse: Matrix<4>,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: Matrix::identity(),
+            inverse: Matrix::identity(),
+        }
+    }
+
+    pub fn translate(self, x: f64, y: f64, z: f64) -> Self {
+        self.then_with(
+            Matrix::translation(x, y, z),
+            Matrix::translation(-x, -y, -z),
+        )
+    }
+
+    pub fn scale(self, x: f64, y: f64, z: f64) -> Self {
+        self.then_with(
+            Matrix::scaling(x, y, z),
+            Matrix::scaling(1.0 / x, 1.0 / y, 1.0 / z),
+        )
+    }
+
+    pub fn rotate_x(self, r: f64) -> Self {
+        self.then_with(Matrix::rotation_x(r), Matrix::rotation_x(-r))
+    }
+
+    pub fn rotate_y(self, r: f64) -> Self {
+        self.then_with(Matrix::rotation_y(r), Matrix::rotation_y(-r))
+    }
+
+    pub fn rotate_z(self, r: f64) -> Self {
+        self.then_with(Matrix::rotation_z(r), Matrix::rotation_z(-r))
+    }
+
+    pub fn shear(self, x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Self {
+        self.then_with(
+            Matrix::shearing(x_y, x_z, y_x, y_z, z_x, z_y),
+            shearing_inverse(x_y, x_z, y_x, y_z, z_x, z_y),
+        )
+    }
+
+    /// Looks from `from` toward `to`, for placing a camera.
+    pub fn view(self, from: Point, to: Point, up: Vector) -> Self {
+        let view = Matrix::view_transform(from, to, up);
+        let translation = Matrix::translation(from.x, from.y, from.z);
+
+        // What's left once the translation is undone is an orientation whose
+        // rows are orthogonal, though not necessarily of unit length, so its
+        // inverse is the transpose with each column divided by that row's
+        // squared length.
+        let orientation = view * translation;
+        let mut inverse_orientation = orientation.transpose();
+        for row in 0..3 {
+            let length_squared: f64 = (0..3).map(|col| orientation[row][col].powi(2)).sum();
+            for col in 0..3 {
+                inverse_orientation[col][row] /= length_squared;
+            }
+        }
+
+        self.then_with(view, translation * inverse_orientation)
+    }
+
+    /// Applies `other` after everything in this transform.
+    pub fn then(self, other: Transform) -> Self {
+        self.then_with(other.matrix, other.inverse)
+    }
+
+    fn then_with(self, matrix: Matrix<4>, inverse: Matrix<4>) -> Self {
+        Self {
+            matrix: matrix * self.matrix,
+            inverse: self.inverse * inverse,
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix<4> {
+        self.matrix
+    }
+
+    pub fn inverse(&self) -> Matrix<4> {
+        self.inverse
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Transform> for Matrix<4> {
+    fn from(transform: Transform) -> Self {
+        transform.matrix
+    }
+}
+
+/// A transformation matrix stored together with its inverse and the transpose
+/// of that inverse, so shapes, patterns and cameras compute them only once
+/// instead of for every ray and normal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InvertibleTransform {
+    matrix: Matrix<4>,
+    inverse: Matrix<4>,
+    inverse_transpose: Matrix<4>,
+}
+
+/// A transform that can't be undone, because it flattens space onto a
+/// plane, a line or a point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SingularTransformError;
+
+impl fmt::Display for SingularTransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "transform is not invertible, look for a zero scale or a degenerate shear"
+        )
+    }
+}
+
+impl std::error::Error for SingularTransformError {}
+
+impl InvertibleTransform {
+    /// Panics if `matrix` isn't invertible, see `try_new`.
+    pub fn new(matrix: Matrix<4>) -> Self {
+        Self::with_inverse(matrix, matrix.inverse())
+    }
+
+    pub fn try_new(matrix: Matrix<4>) -> Result<Self, SingularTransformError> {
+        match matrix.try_inverse() {
+            Some(inverse) => Ok(Self::with_inverse(matrix, inverse)),
+            None => Err(SingularTransformError),
+        }
+    }
+
+    fn with_inverse(matrix: Matrix<4>, inverse: Matrix<4>) -> Self {
+        Self {
+            matrix,
+            inverse,
+            inverse_transpose: inverse.transpose(),
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::with_inverse(Matrix::identity(), Matrix::identity())
+    }
+
+    pub fn matrix(&self) -> &Matrix<4> {
+        &self.matrix
+    }
+
+    pub fn inverse(&self) -> &Matrix<4> {
+        &self.inverse
+    }
+
+    /// Transforms normals from object space to the space this transform maps into.
+    pub fn inverse_transpose(&self) -> &Matrix<4> {
+        &self.inverse_transpose
+    }
+}
+
+impl Default for InvertibleTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl TryFrom<Matrix<4>> for InvertibleTransform {
+    type Error = SingularTransformError;
+
+    fn try_from(matrix: Matrix<4>) -> Result<Self, Self::Error> {
+        Self::try_new(matrix)
+    }
+}
+
+/// Reuses the inverse the builder already tracked, once the matrix is known
+/// to be invertible.
+impl TryFrom<Transform> for InvertibleTransform {
+    type Error = SingularTransformError;
+
+    fn try_from(transform: Transform) -> Result<Self, Self::Error> {
+        if !transform.matrix.is_invertible() {
+            return Err(SingularTransformError);
+        }
+        Ok(Self::with_inverse(transform.matrix, transform.inverse))
+    }
+}
+
+/// The inverse of a shearing matrix, from the adjugate of its upper 3x3 part.
+fn shearing_inverse(x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Matrix<4> {
+    let c00 = 1.0 - y_z * z_y;
+    let c01 = y_z * z_x - y_x;
+    let c02 = y_x * z_y - z_x;
+    let det = c00 + x_y * c01 + x_z * c02;
+
+    Matrix::from([
+        [
+            c00 / det,
+            (x_z * z_y - x_y) / det,
+            (x_y * y_z - x_z) / det,
+            0.0,
+        ],
+        [
+            c01 / det,
+            (1.0 - x_z * z_x) / det,
+            (x_z * y_x - y_z) / det,
+            0.0,
+        ],
+        [
+            c02 / det,
+            (x_y * z_x - z_y) / det,
+            (1.0 - x_y * y_x) / det,
+            0.0,
+        ],
+        [0.0, 0.0, 0.0, 1.0],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_apply_operations_in_reading_order() {
+        let p = Point::new(1.0, 0.0, 1.0);
+
+        let t = Transform::identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+
+        assert_eq!(t.matrix() * p, Point::new(15.0, 0.0, 7.0));
+        assert_eq!(
+            t.matrix(),
+            Matrix::translation(10.0, 5.0, 7.0)
+                * Matrix::scaling(5.0, 5.0, 5.0)
+                * Matrix::rotation_x(PI / 2.0)
+        );
+    }
+
+    #[test]
+    fn should_start_from_identity() {
+        let t = Transform::identity();
+
+        assert_eq!(t.matrix(), Matrix::identity());
+        assert_eq!(t.inverse(), Matrix::identity());
+        assert_eq!(Transform::default(), t);
+    }
+
+    #[test]
+    fn should_keep_the_inverse_up_to_date() {
+        let t = Transform::identity()
+            .shear(1.0, 0.5, -0.25, 0.0, 2.0, 0.75)
+            .rotate_y(0.3)
+            .scale(2.0, -3.0, 0.5)
+            .rotate_z(-1.2)
+            .translate(1.0, -2.0, 3.0)
+            .rotate_x(2.5);
+
+        assert_eq!(t.inverse(), t.matrix().inverse());
+        assert_eq!(t.matrix() * t.inverse(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_invert_a_view() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        let t = Transform::identity().view(from, to, up);
+
+        assert_eq!(t.matrix(), Matrix::view_transform(from, to, up));
+        assert_eq!(t.inverse(), t.matrix().inverse());
+    }
+
+    #[test]
+    fn should_chain_transforms() {
+        let first = Transform::identity().scale(2.0, 2.0, 2.0);
+        let second = Transform::identity().translate(0.0, 1.0, 0.0);
+
+        let t = first.then(second);
+
+        assert_eq!(t, first.translate(0.0, 1.0, 0.0));
+        assert_eq!(
+            Matrix::from(t),
+            Matrix::translation(0.0, 1.0, 0.0) * Matrix::scaling(2.0, 2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn should_store_the_inverse_and_its_transpose() {
+        let matrix = Matrix::translation(1.0, 2.0, 3.0) * Matrix::rotation_y(0.5);
+
+        let t = InvertibleTransform::new(matrix);
+
+        assert_eq!(*t.matrix(), matrix);
+        assert_eq!(*t.inverse(), matrix.inverse());
+        assert_eq!(*t.inverse_transpose(), matrix.inverse().transpose());
+    }
+
+    #[test]
+    fn should_reuse_the_inverse_of_a_built_transform() {
+        let built = Transform::identity()
+            .scale(2.0, 4.0, 8.0)
+            .translate(1.0, 0.0, 0.0);
+
+        let t = InvertibleTransform::try_from(built).unwrap();
+
+        assert_eq!(*t.matrix(), built.matrix());
+        assert_eq!(*t.inverse(), built.inverse());
+        assert_eq!(t, InvertibleTransform::new(built.matrix()));
+    }
+
+    #[test]
+    fn should_reject_a_transform_that_scales_by_zero() {
+        let flattened = Transform::identity()
+            .scale(1.0, 0.0, 1.0)
+            .translate(0.0, 2.0, 0.0);
+
+        assert_eq!(
+            InvertibleTransform::try_from(flattened),
+            Err(SingularTransformError)
+        );
+        assert_eq!(
+            InvertibleTransform::try_new(flattened.matrix()),
+            Err(SingularTransformError)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::bounds::BoundingBox;
+use crate::intersection::{Intersection, Intersections};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeProperties};
+use crate::vector::Vector;
+use crate::EPSILON;
+
+/// A flat triangle with its edges and face normal computed up front.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    properties: ShapeProperties,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub normal: Vector,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        Self {
+            properties: ShapeProperties::new(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalize(),
+        }
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection, returning `(t, u, v)` on a hit.
+pub fn intersect_triangle(ray: &Ray, p1: Point, e1: Vector, e2: Vector) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.direction.cross(&e2);
+    let det = e1.dot(&dir_cross_e2);
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(&e1);
+    let v = f * ray.direction.dot(&origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    Some((f * e2.dot(&origin_cross_e1), u, v))
+}
+
+fn triangle_bounds(p1: Point, p2: Point, p3: Point) -> BoundingBox {
+    let mut bounds = BoundingBox::empty();
+    bounds.add_point(p1);
+    bounds.add_point(p2);
+    bounds.add_point(p3);
+    bounds
+}
+
+impl Shape for Triangle {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => Intersections::new(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections::default(),
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point, _hit: &Intersection) -> Vector {
+        self.normal
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+/// A triangle whose normal is interpolated from the normals at its vertices.
+#[derive(Debug, Clone)]
+pub struct SmoothTriangle {
+    properties: ShapeProperties,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub n1: Vector,
+    pub n2: Vector,
+    pub n3: Vector,
+    pub e1: Vector,
+    pub e2: Vector,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Point, p2: Point, p3: Point, n1: Vector, n2: Vector, n3: Vector) -> Self {
+        Self {
+            properties: ShapeProperties::new(),
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        }
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn properties(&self) -> &ShapeProperties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => Intersections::new(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections::default(),
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point, hit: &Intersection) -> Vector {
+        self.n2 * hit.u + self.n3 * hit.v + self.n1 * (1.0 - hit.u - hit.v)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equal;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    fn smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn should_construct_a_triangle() {
+        let t = triangle();
+
+        assert_eq!(t.e1, Vector::new(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2, Vector::new(1.0, -1.0, 0.0));
+        assert_eq!(t.normal, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_find_the_normal_on_a_triangle() {
+        let t = triangle();
+        let hit = Intersection::new(0.0, &t);
+
+        assert_eq!(t.local_normal_at(Point::new(0.0, 0.5, 0.0), &hit), t.normal);
+        assert_eq!(
+            t.local_normal_at(Point::new(-0.5, 0.75, 0.0), &hit),
+            t.normal
+        );
+        assert_eq!(
+            t.local_normal_at(Point::new(0.5, 0.25, 0.0), &hit),
+            t.normal
+        );
+    }
+
+    #[test]
+    fn should_miss_with_a_ray_parallel_to_the_triangle() {
+        let t = triangle();
+        let ray = Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 0.0));
+
+        assert!(t.local_intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_miss_past_each_edge() {
+        let t = triangle();
+        let origins = [
+            Point::new(1.0, 1.0, -2.0),
+            Point::new(-1.0, 1.0, -2.0),
+            Point::new(0.0, -1.0, -2.0),
+        ];
+
+        for origin in origins {
+            let ray = Ray::new(origin, Vector::new(0.0, 0.0, 1.0));
+            assert!(t.local_intersect(&ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn should_strike_a_triangle() {
+        let t = triangle();
+        let ray = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = t.local_intersect(&ray);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 2.0);
+    }
+
+    #[test]
+    fn should_construct_a_smooth_triangle() {
+        let tri = smooth_triangle();
+
+        assert_eq!(tri.p1, Point::new(0.0, 1.0, 0.0));
+        assert_eq!(tri.p2, Point::new(-1.0, 0.0, 0.0));
+        assert_eq!(tri.p3, Point::new(1.0, 0.0, 0.0));
+        assert_eq!(tri.n1, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(tri.n2, Vector::new(-1.0, 0.0, 0.0));
+        assert_eq!(tri.n3, Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_store_u_and_v_when_intersecting_a_smooth_triangle() {
+        let tri = smooth_triangle();
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = tri.local_intersect(&ray);
+
+        assert!(equal(xs[0].u, 0.45));
+        assert!(equal(xs[0].v, 0.25));
+    }
+
+    #[test]
+    fn should_interpolate_the_normal_of_a_smooth_triangle() {
+        let tri = smooth_triangle();
+        let hit = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+
+        let n = tri.normal_at(Point::new(0.0, 0.0, 0.0), &hit);
+
+        assert_eq!(n, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn should_prepare_the_normal_on_a_smooth_triangle() {
+        let tri = smooth_triangle();
+        let hit = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let ray = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        let comps = hit.prepare_computations(&ray, &Intersections::new(vec![hit]));
+
+        assert_eq!(comps.normalv, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn should_have_a_bounding_box() {
+        let t = Triangle::new(
+            Point::new(-3.0, 7.0, 2.0),
+            Point::new(6.0, 2.0, -4.0),
+            Point::new(2.0, -1.0, -1.0),
+        );
+
+        let bounds = t.bounds();
+
+        assert_eq!(bounds.min, Point::new(-3.0, -1.0, -4.0));
+        assert_eq!(bounds.max, Point::new(6.0, 7.0, 2.0));
+    }
+}
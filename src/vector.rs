@@ -38,6 +38,10 @@ impl Vector {
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    pub fn reflect(&self, normal: &Self) -> Self {
+        *self - *normal * 2.0 * self.dot(normal)
+    }
 }
 
 impl PartialEq for Vector {
@@ -264,3 +268,25 @@ fn should_calculate_cross_product_of_two_vectors() {
 
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_reflect_a_vector_approaching_at_45_degrees() {
+    let v = Vector::new(1.0, -1.0, 0.0);
+    let n = Vector::new(0.0, 1.0, 0.0);
+
+    let expected = Vector::new(1.0, 1.0, 0.0);
+    let actual = v.reflect(&n);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_reflect_a_vector_off_a_slanted_surface() {
+    let v = Vector::new(0.0, -1.0, 0.0);
+    let n = Vector::new(2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0);
+
+    let expected = Vector::new(1.0, 0.0, 0.0);
+    let actual = v.reflect(&n);
+
+    assert_eq!(expected, actual);
+}
@@ -0,0 +1,630 @@
+use crate::color::Color;
+use crate::intersection::{Computations, Intersections};
+use crate::light::{lighting, PointLight};
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::shape::Shape;
+use std::time::{Duration, Instant};
+
+/// The largest number of children a group keeps before it is split up.
+pub const BVH_THRESHOLD: usize = 4;
+
+/// How many reflected or refracted bounces are traced by default.
+pub const MAX_DEPTH: usize = 5;
+
+#[derive(Debug)]
+pub struct World {
+    pub objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<PointLight>,
+    /// The remaining depth `color_at` starts with.
+    pub max_depth: usize,
+    bvh_build_time: Duration,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            max_depth: MAX_DEPTH,
+            bvh_build_time: Duration::ZERO,
+        }
+    }
+
+    pub fn add_object(&mut self, object: impl Shape + 'static) {
+        self.add_boxed_object(Box::new(object));
+    }
+
+    /// Adds `object`, building a bounding volume hierarchy for it if it is a group.
+    pub fn add_boxed_object(&mut self, mut object: Box<dyn Shape>) {
+        let start = Instant::now();
+        object.divide(BVH_THRESHOLD);
+        self.bvh_build_time += start.elapsed();
+
+        self.objects.push(object);
+    }
+
+    /// Total time spent building bounding volume hierarchies for the objects added so far.
+    pub fn bvh_build_time(&self) -> Duration {
+        self.bvh_build_time
+    }
+
+    pub fn add_light(&mut self, light: PointLight) {
+        self.lights.push(light);
+    }
+
+    /// Intersects `ray` with every object, merged into one sorted list.
+    pub fn intersect_world(&self, ray: &Ray) -> Intersections<'_> {
+        let mut intersections = Intersections::default();
+        for object in &self.objects {
+            intersections.extend(object.intersect(ray));
+        }
+        intersections
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let surface = self
+            .lights
+            .iter()
+            .map(|light| {
+                lighting(
+                    comps.object.material(),
+                    comps.object,
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    self.is_shadowed(light, comps.over_point),
+                )
+            })
+            .fold(Color::new(0.0, 0.0, 0.0), |acc, color| acc + color);
+
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        let material = comps.object.material();
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.object.material().reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let ray = Ray::new(comps.over_point, comps.reflectv);
+        self.color_at_depth(&ray, remaining - 1) * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let transparency = comps.object.material().transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        // Snell's law, solved for the angle of the refracted ray.
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(&comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            // Total internal reflection.
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let ray = Ray::new(comps.under_point, direction);
+        self.color_at_depth(&ray, remaining - 1) * transparency
+    }
+
+    /// Casts a shadow ray from `point` toward `light`, ignoring objects that don't cast shadows.
+    pub fn is_shadowed(&self, light: &PointLight, point: Point) -> bool {
+        if !light.casts_shadows {
+            return false;
+        }
+
+        let v = light.position - point;
+        let distance = v.magnitude();
+        let ray = Ray::new(point, v.normalize());
+
+        self.intersect_world(&ray)
+            .iter()
+            .filter(|i| i.object.casts_shadow())
+            .find(|i| i.t >= 0.0)
+            .is_some_and(|hit| hit.t < distance)
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        self.color_at_depth(ray, self.max_depth)
+    }
+
+    /// Like `color_at`, tracing at most `remaining` further reflections or refractions.
+    pub fn color_at_depth(&self, ray: &Ray, remaining: usize) -> Color {
+        let intersections = self.intersect_world(ray);
+        match intersections.hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray, &intersections), remaining),
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::group::Group;
+    use crate::intersection::Intersection;
+    use crate::material::Material;
+    use crate::matrix::Matrix;
+    use crate::pattern::tests::TestPattern;
+    use crate::plane::Plane;
+    use crate::sphere::Sphere;
+    use crate::stats;
+    use crate::vector::Vector;
+    use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+    use std::sync::Arc;
+
+    /// The two concentric spheres and single light used throughout the book.
+    pub fn default_world() -> World {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+
+        let mut s1 = Sphere::new();
+        let mut material = Material::new();
+        material.color = Color::new(0.8, 1.0, 0.6);
+        material.diffuse = 0.7;
+        material.specular = 0.2;
+        s1.set_material(material);
+        world.add_object(s1);
+
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix::scaling(0.5, 0.5, 0.5));
+        world.add_object(s2);
+
+        world
+    }
+
+    #[test]
+    fn should_create_an_empty_world() {
+        let world = World::new();
+
+        assert!(world.objects.is_empty());
+        assert!(world.lights.is_empty());
+    }
+
+    #[test]
+    fn should_divide_groups_when_they_are_added() {
+        let mut group = Group::new();
+        for i in 0..(BVH_THRESHOLD * 4) {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(Matrix::translation(i as f64 * 3.0, 0.0, 0.0));
+            group.add_child(sphere);
+        }
+        let mut world = World::new();
+
+        world.add_object(group);
+
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        stats::take();
+        let xs = world.intersect_world(&ray);
+        assert_eq!(xs.len(), 2);
+        assert!(stats::take().primitive_tests <= BVH_THRESHOLD as u64);
+    }
+
+    #[test]
+    fn should_intersect_a_world_with_a_ray() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = world.intersect_world(&ray);
+
+        let ts: Vec<f64> = xs.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn should_shade_an_intersection() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = world.objects[0].as_ref();
+        let i = Intersection::new(4.0, shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.38066, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn should_shade_an_intersection_from_the_inside() {
+        let mut world = default_world();
+        world.lights = vec![PointLight::new(
+            Point::new(0.0, 0.25, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        )];
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let shape = world.objects[1].as_ref();
+        let i = Intersection::new(0.5, shape);
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.90498, 0.90498, 0.90498)
+        );
+    }
+
+    #[test]
+    fn should_sum_the_contribution_of_every_light() {
+        let mut world = default_world();
+        let light = world.lights[0];
+        world.add_light(light);
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, world.objects[0].as_ref());
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.38066, 0.47583, 0.2855) * 2.0
+        );
+    }
+
+    #[test]
+    fn should_be_black_when_a_ray_misses() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+        assert_eq!(world.color_at(&ray), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_color_when_a_ray_hits() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_eq!(world.color_at(&ray), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn should_color_with_an_intersection_behind_the_ray() {
+        let mut world = default_world();
+        for object in world.objects.iter_mut() {
+            let mut material = object.material().clone();
+            material.ambient = 1.0;
+            object.set_material(material);
+        }
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
+
+        let inner_color = world.objects[1].material().color;
+
+        assert_eq!(world.color_at(&ray), inner_color);
+    }
+
+    #[test]
+    fn should_not_shadow_when_nothing_is_collinear_with_point_and_light() {
+        let world = default_world();
+        let p = Point::new(0.0, 10.0, 0.0);
+
+        assert!(!world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_shadow_when_an_object_is_between_the_point_and_the_light() {
+        let world = default_world();
+        let p = Point::new(10.0, -10.0, 10.0);
+
+        assert!(world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_not_shadow_when_an_object_is_behind_the_light() {
+        let world = default_world();
+        let p = Point::new(-20.0, 20.0, -20.0);
+
+        assert!(!world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_not_shadow_when_an_object_is_behind_the_point() {
+        let world = default_world();
+        let p = Point::new(-2.0, 2.0, -2.0);
+
+        assert!(!world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_not_shadow_with_objects_that_do_not_cast_shadows() {
+        let mut world = default_world();
+        for object in world.objects.iter_mut() {
+            object.set_casts_shadow(false);
+        }
+        let p = Point::new(10.0, -10.0, 10.0);
+
+        assert!(!world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_not_shadow_with_lights_that_do_not_cast_shadows() {
+        let mut world = default_world();
+        world.lights[0].casts_shadows = false;
+        let p = Point::new(10.0, -10.0, 10.0);
+
+        assert!(!world.is_shadowed(&world.lights[0], p));
+    }
+
+    #[test]
+    fn should_shade_an_intersection_in_shadow() {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        world.add_object(Sphere::new());
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix::translation(0.0, 0.0, 10.0));
+        world.add_object(s2);
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, world.objects[1].as_ref());
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.1, 0.1, 0.1)
+        );
+    }
+
+    fn set_material(object: &mut dyn Shape, change: impl FnOnce(&mut Material)) {
+        let mut material = object.material().clone();
+        change(&mut material);
+        object.set_material(material);
+    }
+
+    fn floor(transform: Matrix<4>, change: impl FnOnce(&mut Material)) -> Plane {
+        let mut floor = Plane::new();
+        floor.set_transform(transform);
+        set_material(&mut floor, change);
+        floor
+    }
+
+    #[test]
+    fn should_not_reflect_off_a_nonreflective_material() {
+        let mut world = default_world();
+        set_material(world.objects[1].as_mut(), |m| m.ambient = 1.0);
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(1.0, world.objects[1].as_ref());
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.reflected_color(&comps, MAX_DEPTH),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_reflect_off_a_reflective_material() {
+        let mut world = default_world();
+        world.add_object(floor(Matrix::translation(0.0, -1.0, 0.0), |m| {
+            m.reflective = 0.5
+        }));
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, world.objects[2].as_ref());
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(
+            world.reflected_color(&comps, MAX_DEPTH),
+            Color::new(0.19033, 0.23791, 0.14274)
+        );
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.87675, 0.92434, 0.82917)
+        );
+    }
+
+    #[test]
+    fn should_terminate_between_mutually_reflective_surfaces() {
+        let mut world = World::new();
+        world.add_light(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        world.add_object(floor(Matrix::translation(0.0, -1.0, 0.0), |m| {
+            m.reflective = 1.0
+        }));
+        world.add_object(floor(Matrix::translation(0.0, 1.0, 0.0), |m| {
+            m.reflective = 1.0
+        }));
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+
+        let color = world.color_at(&ray);
+
+        assert!(color.r > 0.0);
+    }
+
+    #[test]
+    fn should_not_reflect_at_the_maximum_recursive_depth() {
+        let mut world = default_world();
+        world.add_object(floor(Matrix::translation(0.0, -1.0, 0.0), |m| {
+            m.reflective = 0.5
+        }));
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let i = Intersection::new(SQRT_2, world.objects[2].as_ref());
+
+        let comps = i.prepare_computations(&ray, &Intersections::new(vec![i]));
+
+        assert_eq!(world.reflected_color(&comps, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_not_refract_through_an_opaque_surface() {
+        let world = default_world();
+        let shape = world.objects[0].as_ref();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(4.0, shape),
+            Intersection::new(6.0, shape),
+        ]);
+
+        let comps = xs[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(
+            world.refracted_color(&comps, MAX_DEPTH),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_not_refract_at_the_maximum_recursive_depth() {
+        let mut world = default_world();
+        set_material(world.objects[0].as_mut(), |m| {
+            m.transparency = 1.0;
+            m.refractive_index = 1.5;
+        });
+        let shape = world.objects[0].as_ref();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(4.0, shape),
+            Intersection::new(6.0, shape),
+        ]);
+
+        let comps = xs[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(world.refracted_color(&comps, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_not_refract_under_total_internal_reflection() {
+        let mut world = default_world();
+        set_material(world.objects[0].as_mut(), |m| {
+            m.transparency = 1.0;
+            m.refractive_index = 1.5;
+        });
+        let shape = world.objects[0].as_ref();
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, FRAC_1_SQRT_2),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let xs = Intersections::new(vec![
+            Intersection::new(-FRAC_1_SQRT_2, shape),
+            Intersection::new(FRAC_1_SQRT_2, shape),
+        ]);
+
+        let comps = xs[1].prepare_computations(&ray, &xs);
+
+        assert_eq!(
+            world.refracted_color(&comps, MAX_DEPTH),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_refract_a_ray() {
+        let mut world = default_world();
+        set_material(world.objects[0].as_mut(), |m| {
+            m.ambient = 1.0;
+            m.pattern = Some(Arc::new(TestPattern::new()));
+        });
+        set_material(world.objects[1].as_mut(), |m| {
+            m.transparency = 1.0;
+            m.refractive_index = 1.5;
+        });
+        let a = world.objects[0].as_ref();
+        let b = world.objects[1].as_ref();
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.1), Vector::new(0.0, 1.0, 0.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(-0.9899, a),
+            Intersection::new(-0.4899, b),
+            Intersection::new(0.4899, b),
+            Intersection::new(0.9899, a),
+        ]);
+
+        let comps = xs[2].prepare_computations(&ray, &xs);
+
+        // The book's 0.04725 assumes a larger EPSILON for `under_point`.
+        assert_eq!(
+            world.refracted_color(&comps, MAX_DEPTH),
+            Color::new(0.0, 0.99888, 0.04722)
+        );
+    }
+
+    #[test]
+    fn should_shade_a_transparent_material() {
+        let mut world = default_world();
+        world.add_object(floor(Matrix::translation(0.0, -1.0, 0.0), |m| {
+            m.transparency = 0.5;
+            m.refractive_index = 1.5;
+        }));
+        let mut ball = Sphere::new();
+        ball.set_transform(Matrix::translation(0.0, -3.5, -0.5));
+        set_material(&mut ball, |m| {
+            m.color = Color::new(1.0, 0.0, 0.0);
+            m.ambient = 0.5;
+        });
+        world.add_object(ball);
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let xs = Intersections::new(vec![Intersection::new(SQRT_2, world.objects[2].as_ref())]);
+
+        let comps = xs[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.93642, 0.68642, 0.68642)
+        );
+    }
+
+    #[test]
+    fn should_shade_a_reflective_transparent_material_with_schlick() {
+        let mut world = default_world();
+        world.add_object(floor(Matrix::translation(0.0, -1.0, 0.0), |m| {
+            m.reflective = 0.5;
+            m.transparency = 0.5;
+            m.refractive_index = 1.5;
+        }));
+        let mut ball = Sphere::new();
+        ball.set_transform(Matrix::translation(0.0, -3.5, -0.5));
+        set_material(&mut ball, |m| {
+            m.color = Color::new(1.0, 0.0, 0.0);
+            m.ambient = 0.5;
+        });
+        world.add_object(ball);
+        let ray = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        );
+        let xs = Intersections::new(vec![Intersection::new(SQRT_2, world.objects[2].as_ref())]);
+
+        let comps = xs[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.93391, 0.69643, 0.69243)
+        );
+    }
+}
@@ -0,0 +1,538 @@
+use std::fmt;
+
+/// The subset of YAML used by scene files: block mappings and sequences,
+/// single line flow collections (`[1, 2]`, `{ a: 1 }`), plain and quoted
+/// scalars, and `#` comments. Anchors, tags and multi-line scalars are not
+/// supported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Scalar(String),
+    Sequence(Vec<Node>),
+    /// Entries in the order they were written.
+    Mapping(Vec<(String, Node)>),
+}
+
+/// A value along with the line it started on, for error messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub line: usize,
+    pub value: Value,
+}
+
+impl Node {
+    fn new(line: usize, value: Value) -> Self {
+        Self { line, value }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::Scalar(scalar) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(String, Node)]> {
+        match &self.value {
+            Value::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The value of `key`, if this is a mapping that has it.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, node)| node)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+fn error<T>(line: usize, message: impl Into<String>) -> Result<T, YamlError> {
+    Err(YamlError {
+        line,
+        message: message.into(),
+    })
+}
+
+pub fn parse(source: &str) -> Result<Node, YamlError> {
+    let mut lines = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        let number = index + 1;
+        let text = strip_comment(raw).trim_end();
+        let content = text.trim_start_matches(' ');
+        if content.is_empty() {
+            continue;
+        }
+        if content.starts_with('\t') {
+            return error(number, "tabs can't be used for indentation");
+        }
+        lines.push(Line {
+            number,
+            indent: text.len() - content.len(),
+            text: content,
+        });
+    }
+
+    let Some(first) = lines.first() else {
+        return Ok(Node::new(1, Value::Null));
+    };
+    let indent = first.indent;
+    let mut parser = Parser { lines, position: 0 };
+
+    let root = parser.block(indent)?;
+    match parser.lines.get(parser.position) {
+        Some(line) => error(line.number, "unexpected indentation"),
+        None => Ok(root),
+    }
+}
+
+/// Removes a trailing `#` comment, leaving `#` inside quotes alone.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() => return &line[..index],
+            _ => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Splits `key: value`, returning `None` for anything that isn't a mapping entry.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    if text.starts_with(['[', '{', '"', '\'']) {
+        return None;
+    }
+    match text.find(": ") {
+        Some(index) => Some((text[..index].trim(), text[index + 2..].trim())),
+        None => text.strip_suffix(':').map(|key| (key.trim(), "")),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn block(&mut self, indent: usize) -> Result<Node, YamlError> {
+        let line = self.lines[self.position];
+        if is_item(line.text) {
+            self.sequence(indent)
+        } else if split_key(line.text).is_some() {
+            self.mapping(indent)
+        } else {
+            self.position += 1;
+            flow(line.text, line.number)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Node, YamlError> {
+        let start = self.lines[self.position].number;
+        let mut items = Vec::new();
+
+        while let Some(&line) = self.lines.get(self.position) {
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                return error(line.number, "unexpected indentation");
+            }
+            if !is_item(line.text) {
+                break;
+            }
+
+            let rest = line.text[1..].trim_start();
+            if rest.is_empty() {
+                self.position += 1;
+                items.push(self.nested(indent, line.number, false)?);
+            } else {
+                // Treat whatever follows the dash as a block of its own, so
+                // the following lines of a mapping can line up with its first key.
+                let offset = line.text.len() - rest.len();
+                self.lines[self.position] = Line {
+                    number: line.number,
+                    indent: indent + offset,
+                    text: rest,
+                };
+                items.push(self.block(indent + offset)?);
+            }
+        }
+
+        Ok(Node::new(start, Value::Sequence(items)))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Node, YamlError> {
+        let start = self.lines[self.position].number;
+        let mut entries: Vec<(String, Node)> = Vec::new();
+
+        while let Some(&line) = self.lines.get(self.position) {
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                return error(line.number, "unexpected indentation");
+            }
+            if is_item(line.text) {
+                return error(line.number, "expected `key: value`, found a list item");
+            }
+            let Some((key, rest)) = split_key(line.text) else {
+                return error(line.number, "expected `key: value`");
+            };
+            let key = unquote(key);
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return error(line.number, format!("duplicate key `{}`", key));
+            }
+
+            self.position += 1;
+            let value = if rest.is_empty() {
+                self.nested(indent, line.number, true)?
+            } else {
+                flow(rest, line.number)?
+            };
+            entries.push((key, value));
+        }
+
+        Ok(Node::new(start, Value::Mapping(entries)))
+    }
+
+    /// The block below a key or dash with nothing after it, if there is one.
+    fn nested(&mut self, indent: usize, line: usize, in_mapping: bool) -> Result<Node, YamlError> {
+        match self.lines.get(self.position) {
+            Some(next) if next.indent > indent => self.block(next.indent),
+            // A list may sit at the same indentation as the key it belongs to.
+            Some(next) if in_mapping && next.indent == indent && is_item(next.text) => {
+                self.sequence(indent)
+            }
+            _ => Ok(Node::new(line, Value::Null)),
+        }
+    }
+}
+
+fn unquote(text: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner.to_string();
+        }
+    }
+    text.to_string()
+}
+
+/// Parses a value written on a single line.
+fn flow(text: &str, line: usize) -> Result<Node, YamlError> {
+    let mut parser = FlowParser {
+        chars: text.chars().collect(),
+        position: 0,
+        line,
+    };
+    let value = parser.value(false)?;
+    parser.skip_whitespace();
+    if parser.position < parser.chars.len() {
+        return error(
+            line,
+            format!("unexpected `{}`", parser.chars[parser.position]),
+        );
+    }
+    Ok(value)
+}
+
+struct FlowParser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl FlowParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn value(&mut self, in_collection: bool) -> Result<Node, YamlError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('[') => self.sequence(),
+            Some('{') => self.mapping(),
+            Some(quote @ ('"' | '\'')) => self.quoted(quote),
+            _ => {
+                let scalar = self.plain(in_collection);
+                if scalar.is_empty() {
+                    Ok(Node::new(self.line, Value::Null))
+                } else {
+                    Ok(Node::new(self.line, Value::Scalar(scalar)))
+                }
+            }
+        }
+    }
+
+    fn plain(&mut self, in_collection: bool) -> String {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if in_collection && matches!(c, ',' | ']' | '}' | ':') {
+                break;
+            }
+            self.position += 1;
+        }
+        self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<Node, YamlError> {
+        self.position += 1;
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if c == quote {
+                let text = self.chars[start..self.position].iter().collect();
+                self.position += 1;
+                return Ok(Node::new(self.line, Value::Scalar(text)));
+            }
+            self.position += 1;
+        }
+        error(self.line, "unterminated string")
+    }
+
+    /// Consumes `,` between items, or `close` at the end. Returns whether the collection ended.
+    fn separator(&mut self, close: char) -> Result<bool, YamlError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.position += 1;
+                Ok(false)
+            }
+            Some(c) if c == close => {
+                self.position += 1;
+                Ok(true)
+            }
+            Some(c) => error(
+                self.line,
+                format!("expected `,` or `{}`, found `{}`", close, c),
+            ),
+            None => error(self.line, format!("missing `{}`", close)),
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Node, YamlError> {
+        self.position += 1;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Node::new(self.line, Value::Sequence(items)));
+        }
+        loop {
+            items.push(self.value(true)?);
+            if self.separator(']')? {
+                return Ok(Node::new(self.line, Value::Sequence(items)));
+            }
+        }
+    }
+
+    fn mapping(&mut self) -> Result<Node, YamlError> {
+        self.position += 1;
+        let mut entries: Vec<(String, Node)> = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Node::new(self.line, Value::Mapping(entries)));
+        }
+        loop {
+            let key = match self.value(true)?.value {
+                Value::Scalar(key) => key,
+                _ => return error(self.line, "expected a key"),
+            };
+            self.skip_whitespace();
+            if self.peek() != Some(':') {
+                return error(self.line, format!("expected `:` after `{}`", key));
+            }
+            self.position += 1;
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return error(self.line, format!("duplicate key `{}`", key));
+            }
+            let value = self.value(true)?;
+            entries.push((key, value));
+
+            if self.separator('}')? {
+                return Ok(Node::new(self.line, Value::Mapping(entries)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(line: usize, text: &str) -> Node {
+        Node::new(line, Value::Scalar(text.to_string()))
+    }
+
+    fn scalars(node: &Node) -> Vec<&str> {
+        node.as_sequence()
+            .unwrap()
+            .iter()
+            .map(|item| item.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_a_mapping_of_scalars() {
+        let doc = parse("width: 100\nname: 'my scene'\nempty:\n").unwrap();
+
+        assert_eq!(doc.get("width"), Some(&scalar(1, "100")));
+        assert_eq!(doc.get("name"), Some(&scalar(2, "my scene")));
+        assert_eq!(doc.get("empty").unwrap().value, Value::Null);
+        assert_eq!(doc.get("missing"), None);
+    }
+
+    #[test]
+    fn should_parse_a_list_of_mappings() {
+        let source = "\
+# A comment
+- add: camera
+  width: 100   # trailing comment
+  from: [ -6, 6, -10 ]
+
+- add: light
+  at: [50, 100, -50]
+";
+
+        let doc = parse(source).unwrap();
+
+        let items = doc.as_sequence().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].line, 2);
+        assert_eq!(items[0].get("width"), Some(&scalar(3, "100")));
+        assert_eq!(scalars(items[0].get("from").unwrap()), ["-6", "6", "-10"]);
+        assert_eq!(items[1].get("add"), Some(&scalar(6, "light")));
+        assert_eq!(items[1].get("at").unwrap().line, 7);
+    }
+
+    #[test]
+    fn should_parse_nested_blocks() {
+        let source = "\
+- define: standard-transform
+  value:
+    - [ translate, 1, -1, 1 ]
+    - [ scale, 0.5, 0.5, 0.5 ]
+- define: white-material
+  value:
+    color: [ 1, 1, 1 ]
+    diffuse: 0.7
+- add: group
+  children:
+  - add: sphere
+  - add: cube
+";
+
+        let doc = parse(source).unwrap();
+
+        let items = doc.as_sequence().unwrap();
+        let transform = items[0].get("value").unwrap().as_sequence().unwrap();
+        assert_eq!(scalars(&transform[1]), ["scale", "0.5", "0.5", "0.5"]);
+        assert_eq!(transform[1].line, 4);
+        let material = items[1].get("value").unwrap();
+        assert_eq!(material.get("diffuse"), Some(&scalar(8, "0.7")));
+        let children = items[2].get("children").unwrap().as_sequence().unwrap();
+        assert_eq!(children[1].get("add"), Some(&scalar(12, "cube")));
+    }
+
+    #[test]
+    fn should_parse_flow_mappings() {
+        let doc =
+            parse("material: { color: [1, 0.5, 0], 'shine': 10, note: \"a, b # c\" }").unwrap();
+
+        let material = doc.get("material").unwrap();
+        assert_eq!(scalars(material.get("color").unwrap()), ["1", "0.5", "0"]);
+        assert_eq!(material.get("shine"), Some(&scalar(1, "10")));
+        assert_eq!(material.get("note"), Some(&scalar(1, "a, b # c")));
+    }
+
+    #[test]
+    fn should_parse_lists_of_lists() {
+        let doc = parse("- - 1\n  - 2\n- []\n").unwrap();
+
+        let items = doc.as_sequence().unwrap();
+        assert_eq!(scalars(&items[0]), ["1", "2"]);
+        assert_eq!(items[1].as_sequence(), Some(&[][..]));
+    }
+
+    #[test]
+    fn should_parse_an_empty_document() {
+        assert_eq!(parse("# nothing\n\n").unwrap().value, Value::Null);
+    }
+
+    #[test]
+    fn should_report_the_line_of_errors() {
+        let cases = [
+            ("a: 1\n  b: 2\n", 2, "unexpected indentation"),
+            ("a: 1\na: 2\n", 2, "duplicate key `a`"),
+            ("a: [1, 2\n", 1, "missing `]`"),
+            (
+                "- a: 1\n\n  - b\n",
+                3,
+                "expected `key: value`, found a list item",
+            ),
+            ("a: 1\n\tb: 2\n", 2, "tabs can't be used for indentation"),
+            ("a: \"open\n", 1, "unterminated string"),
+            ("a: [1] 2\n", 1, "unexpected `2`"),
+        ];
+
+        for (source, line, message) in cases {
+            let expected = YamlError {
+                line,
+                message: message.to_string(),
+            };
+            assert_eq!(parse(source), Err(expected), "{:?}", source);
+        }
+    }
+}